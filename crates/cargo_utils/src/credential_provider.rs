@@ -0,0 +1,405 @@
+//! Read registry tokens with cargo
+//! [credential providers](https://doc.rust-lang.org/cargo/reference/registry-authentication.html).
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write as _},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context as _;
+use secrecy::SecretString;
+use serde::Deserialize;
+
+use crate::registry::{cargo_config_paths, registry_env_var_prefix};
+
+/// Built-in provider that reads the token from the cargo credentials file.
+pub(crate) const TOKEN_PROVIDER: &str = "cargo:token";
+/// Built-in provider that runs a command and reads the token from its stdout.
+const TOKEN_FROM_STDOUT_PROVIDER: &str = "cargo:token-from-stdout";
+/// Version of the credential provider protocol.
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Credential provider settings of the cargo config.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CredentialProviders {
+    /// `registry.global-credential-providers`, from the lowest to the highest precedence.
+    global: Vec<Vec<String>>,
+    /// `registries.<name>.credential-provider` and `registry.credential-provider` (crates.io).
+    /// The key is [`Option::None`] for crates.io.
+    registries: HashMap<Option<String>, Vec<String>>,
+    /// `credential-alias`
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl CredentialProviders {
+    /// Read the credential providers from the cargo config files that apply to the manifest
+    /// and from the environment variables.
+    pub(crate) fn read(manifest_path: &Path) -> anyhow::Result<Self> {
+        let mut providers = Self::default();
+        // Read the files from the lowest to the highest precedence.
+        for config_path in cargo_config_paths(manifest_path)?.iter().rev() {
+            let content =
+                fs_err::read_to_string(config_path).context("failed to read cargo config file")?;
+            let config = toml::from_str::<CredentialConfig>(&content)
+                .with_context(|| format!("invalid cargo config {config_path:?}"))?;
+            providers.merge(config);
+        }
+        providers.merge_env();
+        Ok(providers)
+    }
+
+    fn merge(&mut self, config: CredentialConfig) {
+        self.global.extend(
+            config
+                .registry
+                .global_credential_providers
+                .iter()
+                .map(|provider| split_command(provider)),
+        );
+        if let Some(provider) = config.registry.credential_provider {
+            self.registries.insert(None, provider.into_args());
+        }
+        for (name, registry) in config.registries {
+            if let Some(provider) = registry.credential_provider {
+                self.registries.insert(Some(name), provider.into_args());
+            }
+        }
+        for (name, alias) in config.credential_alias {
+            self.aliases.insert(name, alias.into_args());
+        }
+    }
+
+    fn merge_env(&mut self) {
+        if let Ok(providers) = std::env::var("CARGO_REGISTRY_GLOBAL_CREDENTIAL_PROVIDERS") {
+            self.global
+                .extend(providers.split_whitespace().map(|p| vec![p.to_string()]));
+        }
+        if let Ok(provider) = std::env::var("CARGO_REGISTRY_CREDENTIAL_PROVIDER") {
+            self.registries.insert(None, split_command(&provider));
+        }
+    }
+
+    /// Providers to try for the registry, from the highest to the lowest precedence.
+    /// Aliases are resolved.
+    pub(crate) fn for_registry(&self, registry: Option<&str>) -> Vec<Vec<String>> {
+        // `CARGO_REGISTRIES_<name>_CREDENTIAL_PROVIDER` overrides the cargo config.
+        let registry_provider = registry
+            .and_then(|name| {
+                let env_var = format!(
+                    "{}_CREDENTIAL_PROVIDER",
+                    registry_env_var_prefix(name).ok()?
+                );
+                std::env::var(env_var).ok().map(|p| split_command(&p))
+            })
+            .or_else(|| self.registries.get(&registry.map(str::to_string)).cloned());
+        let providers = match registry_provider {
+            Some(provider) => vec![provider],
+            None if self.global.is_empty() => vec![vec![TOKEN_PROVIDER.to_string()]],
+            None => self.global.iter().rev().cloned().collect(),
+        };
+        providers
+            .into_iter()
+            .map(|provider| self.resolve_alias(provider))
+            .collect()
+    }
+
+    fn resolve_alias(&self, provider: Vec<String>) -> Vec<String> {
+        match provider.split_first() {
+            Some((name, args)) => match self.aliases.get(name) {
+                Some(alias) => alias.iter().chain(args).cloned().collect(),
+                None => provider,
+            },
+            None => provider,
+        }
+    }
+}
+
+/// Registry information sent to the credential provider.
+pub(crate) struct ProviderRegistry<'a> {
+    /// [`Option::None`] means crates.io.
+    pub name: Option<&'a str>,
+    pub index_url: &'a str,
+}
+
+/// Run a credential provider to get a token to read the registry index.
+///
+/// Returns [`Option::None`] if the provider doesn't have a token for the registry
+/// or if it's a built-in provider that release-plz doesn't support.
+/// `cargo:token` is handled by the caller.
+pub(crate) fn provider_token(
+    provider: &[String],
+    registry: &ProviderRegistry,
+) -> anyhow::Result<Option<SecretString>> {
+    let Some((name, args)) = provider.split_first() else {
+        return Ok(None);
+    };
+    match name.as_str() {
+        TOKEN_FROM_STDOUT_PROVIDER => token_from_stdout(args, registry).map(Some),
+        // Other built-in providers, like `cargo:libsecret`, use OS-specific APIs.
+        // `cargo publish` can still use them.
+        name if name.starts_with("cargo:") => Ok(None),
+        path => run_provider(path, args, registry),
+    }
+}
+
+fn token_from_stdout(
+    command: &[String],
+    registry: &ProviderRegistry,
+) -> anyhow::Result<SecretString> {
+    let (program, args) = command
+        .split_first()
+        .with_context(|| format!("{TOKEN_FROM_STDOUT_PROVIDER} requires a command"))?;
+    let output = Command::new(program)
+        .args(args)
+        .env("CARGO_REGISTRY_INDEX_URL", registry.index_url)
+        .envs(registry.name.map(|name| ("CARGO_REGISTRY_NAME_OPT", name)))
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("failed to run credential provider `{program}`"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "credential provider `{program}` exited with {}",
+        output.status
+    );
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| format!("credential provider `{program}` returned invalid UTF-8"))?;
+    let token = stdout.lines().next().unwrap_or_default().trim();
+    anyhow::ensure!(
+        !token.is_empty(),
+        "credential provider `{program}` returned an empty token"
+    );
+    Ok(token.to_string().into())
+}
+
+/// Run an external provider with the
+/// [credential provider protocol](https://doc.rust-lang.org/cargo/reference/credential-provider-protocol.html).
+fn run_provider(
+    path: &str,
+    args: &[String],
+    registry: &ProviderRegistry,
+) -> anyhow::Result<Option<SecretString>> {
+    let mut child = Command::new(path)
+        .arg("--cargo-plugin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("failed to run credential provider `{path}`"))?;
+    let mut stdin = child.stdin.take().context("can't open provider stdin")?;
+    let mut stdout = BufReader::new(child.stdout.take().context("can't open provider stdout")?);
+
+    let response = (|| -> anyhow::Result<ProviderResponse> {
+        let hello: ProviderHello =
+            serde_json::from_str(&read_line(&mut stdout)?).context("invalid hello message")?;
+        anyhow::ensure!(
+            hello.v.contains(&PROTOCOL_VERSION),
+            "unsupported protocol versions {:?}",
+            hello.v
+        );
+        let request = serde_json::json!({
+            "v": PROTOCOL_VERSION,
+            "registry": {
+                "index-url": registry.index_url,
+                "name": registry.name,
+            },
+            "kind": "get",
+            "operation": "read",
+            "args": args,
+        });
+        writeln!(stdin, "{request}")?;
+        stdin.flush()?;
+        serde_json::from_str(&read_line(&mut stdout)?).context("invalid response")
+    })();
+    // Closing stdin tells the provider that there are no more requests.
+    drop(stdin);
+    let status = child.wait()?;
+    let response =
+        response.with_context(|| format!("credential provider `{path}` failed ({status})"))?;
+
+    match response {
+        ProviderResponse::Ok { token } => Ok(Some(token.into())),
+        ProviderResponse::Err { kind, message } => match kind.as_str() {
+            "not-found" | "url-not-supported" | "operation-not-supported" => Ok(None),
+            _ => Err(anyhow::anyhow!(
+                "credential provider `{path}` returned an error: {}",
+                message.unwrap_or(kind)
+            )),
+        },
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> anyhow::Result<String> {
+    let mut line = String::new();
+    let bytes = reader.read_line(&mut line)?;
+    anyhow::ensure!(bytes > 0, "the provider closed its output");
+    Ok(line)
+}
+
+/// Split a provider command written as a single string, like `cargo:token-from-stdout cmd arg`.
+fn split_command(command: &str) -> Vec<String> {
+    command.split_whitespace().map(str::to_string).collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderHello {
+    v: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+enum ProviderResponse {
+    Ok {
+        token: String,
+    },
+    Err {
+        kind: String,
+        message: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct CredentialConfig {
+    #[serde(default)]
+    registry: RegistryCredentialConfig,
+    #[serde(default)]
+    registries: HashMap<String, RegistryCredentialConfig>,
+    #[serde(default)]
+    credential_alias: HashMap<String, ProviderCommand>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct RegistryCredentialConfig {
+    credential_provider: Option<ProviderCommand>,
+    #[serde(default)]
+    global_credential_providers: Vec<String>,
+}
+
+/// A provider can be written as a string or as an array of arguments.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ProviderCommand {
+    String(String),
+    Array(Vec<String>),
+}
+
+impl ProviderCommand {
+    fn into_args(self) -> Vec<String> {
+        match self {
+            Self::String(command) => split_command(&command),
+            Self::Array(args) => args,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::ExposeSecret as _;
+
+    use super::*;
+
+    fn providers(config: &str) -> CredentialProviders {
+        let mut providers = CredentialProviders::default();
+        providers.merge(toml::from_str(config).unwrap());
+        providers
+    }
+
+    fn registry() -> ProviderRegistry<'static> {
+        ProviderRegistry {
+            name: Some("my-registry"),
+            index_url: "sparse+https://example.com/index/",
+        }
+    }
+
+    #[test]
+    fn registry_provider_takes_precedence() {
+        let providers = providers(
+            r#"
+            [registry]
+            global-credential-providers = ["cargo:token", "my-provider --flag"]
+
+            [registries.my-registry]
+            credential-provider = ["cargo:token-from-stdout", "echo", "my token"]
+            "#,
+        );
+        assert_eq!(
+            providers.for_registry(Some("my-registry")),
+            vec![vec!["cargo:token-from-stdout", "echo", "my token"]]
+        );
+        assert_eq!(
+            providers.for_registry(None),
+            vec![vec!["my-provider", "--flag"], vec!["cargo:token"]]
+        );
+    }
+
+    #[test]
+    fn default_provider_is_token() {
+        let providers = providers("");
+        assert_eq!(providers.for_registry(None), vec![vec![TOKEN_PROVIDER]]);
+    }
+
+    #[test]
+    fn alias_is_resolved() {
+        let providers = providers(
+            r#"
+            [registries.my-registry]
+            credential-provider = "my-alias --extra"
+
+            [credential-alias]
+            my-alias = ["/usr/bin/provider", "--flag"]
+            "#,
+        );
+        assert_eq!(
+            providers.for_registry(Some("my-registry")),
+            vec![vec!["/usr/bin/provider", "--flag", "--extra"]]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_is_read_from_stdout() {
+        let provider = split_command("cargo:token-from-stdout sh -c");
+        let provider = [
+            provider,
+            vec!["echo $CARGO_REGISTRY_NAME_OPT-token".to_string()],
+        ]
+        .concat();
+        let token = provider_token(&provider, &registry()).unwrap().unwrap();
+        assert_eq!(token.expose_secret(), "my-registry-token");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_is_read_with_protocol() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let provider_path = temp_dir.path().join("provider.sh");
+        fs_err::write(
+            &provider_path,
+            r#"#!/bin/sh
+echo '{"v":[1]}'
+read request
+case "$request" in
+  *'"operation":"read"'*) echo '{"Ok":{"kind":"get","token":"protocol-token","cache":"session","operation_independent":true}}' ;;
+  *) echo '{"Err":{"kind":"other","message":"unexpected request"}}' ;;
+esac
+"#,
+        )
+        .unwrap();
+        std::process::Command::new("chmod")
+            .arg("+x")
+            .arg(&provider_path)
+            .status()
+            .unwrap();
+        let provider = vec![provider_path.to_str().unwrap().to_string()];
+        let token = provider_token(&provider, &registry()).unwrap().unwrap();
+        assert_eq!(token.expose_secret(), "protocol-token");
+    }
+
+    #[test]
+    fn unsupported_builtin_provider_has_no_token() {
+        let provider = vec!["cargo:libsecret".to_string()];
+        assert!(provider_token(&provider, &registry()).unwrap().is_none());
+    }
+}
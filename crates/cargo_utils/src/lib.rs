@@ -1,3 +1,4 @@
+mod credential_provider;
 mod dependency;
 mod fs_utils;
 mod local_manifest;
@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use url::Url;
 
 const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
-const CRATES_IO_REGISTRY: &str = "crates-io";
+pub(crate) const CRATES_IO_REGISTRY: &str = "crates-io";
 
 /// Read index for a specific registry using environment variables.
 /// <https://doc.rust-lang.org/cargo/reference/environment-variables.html>
@@ -25,6 +25,12 @@ pub fn registry_index_url_from_env(registry: &str) -> anyhow::Result<Option<Stri
 /// - Hyphens (`-`) are converted to underscores (`_`).
 /// - Any other non-alphanumeric character is invalid and will cause an error.
 fn get_registry_env_var_name(registry: &str) -> anyhow::Result<String> {
+    let prefix = registry_env_var_prefix(registry)?;
+    Ok(format!("{prefix}_INDEX"))
+}
+
+/// Prefix of the environment variables of the registry, e.g. `CARGO_REGISTRIES_MY_REGISTRY`.
+pub(crate) fn registry_env_var_prefix(registry: &str) -> anyhow::Result<String> {
     let mut sanitized_name = String::with_capacity(registry.len());
 
     for ch in registry.chars() {
@@ -38,7 +44,7 @@ fn get_registry_env_var_name(registry: &str) -> anyhow::Result<String> {
     }
 
     let sanitized_name = sanitized_name.to_uppercase();
-    Ok(format!("CARGO_REGISTRIES_{sanitized_name}_INDEX"))
+    Ok(format!("CARGO_REGISTRIES_{sanitized_name}"))
 }
 
 /// Find the URL of a registry
@@ -78,33 +84,8 @@ pub fn registry_url(manifest_path: &Path, registry: Option<&str>) -> anyhow::Res
         }
     }
 
-    // ref: https://doc.rust-lang.org/cargo/reference/config.html#hierarchical-structure
-    for work_dir in manifest_path
-        .parent()
-        .expect("there must be a parent directory")
-        .ancestors()
-    {
-        let work_cargo_dir = work_dir.join(".cargo");
-        let config_path = work_cargo_dir.join("config");
-        if config_path.is_file() {
-            read_config(&mut registries, config_path)?;
-        } else {
-            let config_path = work_cargo_dir.join("config.toml");
-            if config_path.is_file() {
-                read_config(&mut registries, config_path)?;
-            }
-        }
-    }
-
-    let default_cargo_home = cargo_home()?;
-    let default_config_path = default_cargo_home.join("config");
-    if default_config_path.is_file() {
-        read_config(&mut registries, default_config_path)?;
-    } else {
-        let default_config_path = default_cargo_home.join("config.toml");
-        if default_config_path.is_file() {
-            read_config(&mut registries, default_config_path)?;
-        }
+    for config_path in cargo_config_paths(manifest_path)? {
+        read_config(&mut registries, config_path)?;
     }
 
     // find head of the relevant linked list
@@ -142,6 +123,28 @@ pub fn registry_url(manifest_path: &Path, registry: Option<&str>) -> anyhow::Res
     Ok(registry_url)
 }
 
+/// Paths of the cargo config files that apply to the manifest,
+/// from the highest to the lowest precedence.
+/// See <https://doc.rust-lang.org/cargo/reference/config.html#hierarchical-structure>
+pub(crate) fn cargo_config_paths(manifest_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    fn config_path(cargo_dir: &Path) -> Option<PathBuf> {
+        ["config", "config.toml"]
+            .into_iter()
+            .map(|file_name| cargo_dir.join(file_name))
+            .find(|path| path.is_file())
+    }
+    let mut paths: Vec<PathBuf> = manifest_path
+        .parent()
+        .expect("there must be a parent directory")
+        .ancestors()
+        .filter_map(|work_dir| config_path(&work_dir.join(".cargo")))
+        .collect();
+    if let Some(default_config_path) = config_path(&cargo_home()?) {
+        paths.push(default_config_path);
+    }
+    Ok(paths)
+}
+
 #[derive(Debug, Deserialize)]
 struct CargoConfig {
     #[serde(default)]
@@ -1,32 +1,78 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context as _;
 use secrecy::SecretString;
 use serde::Deserialize;
 
-pub fn registry_token(registry: Option<&str>) -> anyhow::Result<Option<SecretString>> {
-    let mut token = registry_token_from_env(registry);
-    if token.is_none() {
-        token = registry_token_from_credential_file(registry).with_context(|| {
-            format!(
-                "can't retreive token from credential file for registry `{}`",
-                registry.unwrap_or("crates.io"),
-            )
-        })?;
+use crate::{
+    credential_provider::{CredentialProviders, ProviderRegistry, TOKEN_PROVIDER, provider_token},
+    registry::{CRATES_IO_REGISTRY, registry_env_var_prefix},
+    registry_url,
+};
+
+/// Find the token of the registry, like cargo does:
+/// 1. From the environment variables.
+/// 2. From the credential providers configured in the cargo config files
+///    that apply to `manifest_path`.
+///    If no provider is configured, from the cargo credentials file.
+pub fn registry_token(
+    manifest_path: &Path,
+    registry: Option<&str>,
+) -> anyhow::Result<Option<SecretString>> {
+    if let Some(token) = registry_token_from_env(registry) {
+        return Ok(Some(token));
     }
-    Ok(token)
+    let providers = CredentialProviders::read(manifest_path)?;
+    let mut index_url = None;
+    for provider in providers.for_registry(registry) {
+        let token = if provider == [TOKEN_PROVIDER] {
+            registry_token_from_credential_file(registry).with_context(|| {
+                format!(
+                    "can't retreive token from credential file for registry `{}`",
+                    registry.unwrap_or("crates.io"),
+                )
+            })?
+        } else {
+            let index_url = match &index_url {
+                Some(index_url) => index_url,
+                None => index_url.insert(registry_url(manifest_path, registry)?.to_string()),
+            };
+            let provider_registry = ProviderRegistry {
+                name: registry,
+                index_url,
+            };
+            provider_token(&provider, &provider_registry).with_context(|| {
+                format!(
+                    "can't retrieve token from credential provider for registry `{}`",
+                    registry.unwrap_or("crates.io"),
+                )
+            })?
+        };
+        if token.is_some() {
+            return Ok(token);
+        }
+    }
+    Ok(None)
 }
 
 /// Read credentials for a specific registry using environment variables.
 /// <https://doc.rust-lang.org/cargo/reference/registry-authentication.html#cargotoken>
 pub fn registry_token_from_env(registry: Option<&str>) -> Option<SecretString> {
-    let token = if let Some(r) = registry {
-        let env_var = format!("CARGO_REGISTRIES_{}_TOKEN", r.to_uppercase());
-        std::env::var(env_var)
-    } else {
-        std::env::var("CARGO_REGISTRY_TOKEN")
-    };
-    token.ok().map(|t| t.into())
+    let env_var = registry_token_env_var(registry).ok()?;
+    std::env::var(env_var).ok().map(|t| t.into())
+}
+
+/// Name of the environment variable that cargo reads to get the token of the registry.
+/// [`Option::None`] means crates.io.
+pub fn registry_token_env_var(registry: Option<&str>) -> anyhow::Result<String> {
+    match registry {
+        // Cargo doesn't read `CARGO_REGISTRIES_CRATES_IO_TOKEN`.
+        Some(CRATES_IO_REGISTRY) | None => Ok("CARGO_REGISTRY_TOKEN".to_string()),
+        Some(registry) => Ok(format!("{}_TOKEN", registry_env_var_prefix(registry)?)),
+    }
 }
 
 /// Read credentials for a specific registry using file cargo/credentials.toml.
@@ -37,10 +83,9 @@ pub fn registry_token_from_credential_file(
     let credentials = read_cargo_credentials()?;
     let token = credentials
         .and_then(|c| {
-            let token: Option<RegistryToken> = if let Some(r) = registry {
-                c.registries.get(r).cloned()
-            } else {
-                c.registry.as_ref().cloned()
+            let token: Option<RegistryToken> = match registry {
+                Some(CRATES_IO_REGISTRY) | None => c.registry.as_ref().cloned(),
+                Some(r) => c.registries.get(r).cloned(),
             };
             token
         })
@@ -92,6 +137,31 @@ struct RegistryToken {
 mod tests {
     use super::*;
 
+    #[test]
+    fn token_env_var_follows_cargo_naming() {
+        assert_eq!(
+            registry_token_env_var(None).unwrap(),
+            "CARGO_REGISTRY_TOKEN"
+        );
+        assert_eq!(
+            registry_token_env_var(Some("my-registry")).unwrap(),
+            "CARGO_REGISTRIES_MY_REGISTRY_TOKEN"
+        );
+    }
+
+    #[test]
+    fn token_env_var_of_each_publish_registry() {
+        // A package with `publish = ["internal", "crates-io"]`.
+        let env_vars: Vec<String> = ["internal", "crates-io"]
+            .into_iter()
+            .map(|registry| registry_token_env_var(Some(registry)).unwrap())
+            .collect();
+        assert_eq!(
+            env_vars,
+            ["CARGO_REGISTRIES_INTERNAL_TOKEN", "CARGO_REGISTRY_TOKEN"]
+        );
+    }
+
     #[test]
     fn test_parse_cargo_credentials_both() {
         let sample = r#"
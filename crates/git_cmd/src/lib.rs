@@ -11,7 +11,7 @@ use camino::{Utf8Path, Utf8PathBuf};
 use tracing::{Span, debug, instrument, trace, warn};
 
 /// Repository
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Repo {
     /// Directory where you want to run git operations
     directory: Utf8PathBuf,
@@ -141,6 +141,12 @@ impl Repo {
         Ok(())
     }
 
+    /// Commit with the message and the author of another commit.
+    pub fn commit_signed_reusing_message(&self, commit: &str) -> anyhow::Result<()> {
+        self.git(&["commit", "-s", "--reuse-message", commit])?;
+        Ok(())
+    }
+
     pub fn push(&self, obj: &str) -> anyhow::Result<()> {
         self.git(&["push", &self.original_remote, obj])?;
         Ok(())
@@ -289,7 +295,35 @@ impl Repo {
 
     /// Create a git tag
     pub fn tag(&self, name: &str, message: &str) -> anyhow::Result<String> {
-        self.git(&["tag", "-m", message, name])
+        // Don't strip lines starting with `#`, e.g. markdown headings of the changelog.
+        self.git(&["tag", "--cleanup=whitespace", "-m", message, name])
+    }
+
+    /// Create a git tag signed with the key configured in git.
+    /// See the `user.signingKey` and `gpg.format` git configurations.
+    pub fn signed_tag(&self, name: &str, message: &str) -> anyhow::Result<String> {
+        self.git(&["tag", "--sign", "--cleanup=whitespace", "-m", message, name])
+    }
+
+    /// Returns `true` if the tag contains a GPG, SSH or X.509 signature.
+    /// The signature isn't verified.
+    pub fn is_tag_signed(&self, tag: &str) -> anyhow::Result<bool> {
+        let tag_object = self
+            .git(&["cat-file", "tag", tag])
+            .with_context(|| format!("can't read tag {tag}"))?;
+        let signature_ends = [
+            "-----END PGP SIGNATURE-----",
+            "-----END SSH SIGNATURE-----",
+            "-----END SIGNED MESSAGE-----",
+        ];
+        let tag_object = tag_object.trim_end();
+        Ok(signature_ends.iter().any(|end| tag_object.ends_with(end)))
+    }
+
+    /// Delete a local git tag
+    pub fn delete_tag(&self, name: &str) -> anyhow::Result<()> {
+        self.git(&["tag", "--delete", name])?;
+        Ok(())
     }
 
     /// Get the commit hash of the given tag
@@ -483,6 +517,24 @@ mod tests {
         assert_eq!(repo.current_commit_message().unwrap(), commit_message);
     }
 
+    #[test]
+    fn tag_message_is_kept_and_tag_is_unsigned() {
+        test_logs::init();
+        let repository_dir = tempdir().unwrap();
+        let repo = Repo::init(&repository_dir);
+        let message = "my release\n\n### Added\n\n- new feature";
+        repo.tag("v1.0.0", message).unwrap();
+
+        let tag_message = repo
+            .git(&["tag", "--list", "--format=%(contents)", "v1.0.0"])
+            .unwrap();
+        assert_eq!(tag_message.trim_end(), message);
+        assert!(!repo.is_tag_signed("v1.0.0").unwrap());
+
+        repo.delete_tag("v1.0.0").unwrap();
+        assert!(repo.get_all_tags().is_empty());
+    }
+
     #[test]
     fn clean_project_is_recognized() {
         test_logs::init();
@@ -62,8 +62,11 @@ impl ConfigPath {
 fn load_config(path: &Path) -> anyhow::Result<Option<Config>> {
     match read_to_string(path) {
         Ok(contents) => {
-            let config = toml::from_str(&contents)
+            let mut config: Config = toml::from_str(&contents)
                 .with_context(|| format!("invalid config file {}", path.display()))?;
+            if let Some(config_dir) = path.parent() {
+                config.resolve_paths(config_dir);
+            }
             info!("using release-plz config file {}", path.display());
             Ok(Some(config))
         }
@@ -69,6 +69,20 @@ impl CliArgs {
         };
         Ok(level)
     }
+
+    /// Secrets passed on the command line, which must not appear in the logs.
+    pub fn secrets(&self) -> Vec<&str> {
+        match &self.command {
+            Command::Update(update) => update.secrets(),
+            Command::ReleasePr(release_pr) => release_pr.update.secrets(),
+            Command::Release(release) => release.secrets(),
+            Command::GenerateCompletions(_)
+            | Command::CheckUpdates
+            | Command::GenerateSchema
+            | Command::Init(_)
+            | Command::SetVersion(_) => vec![],
+        }
+    }
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+use cargo_utils::to_utf8_pathbuf;
 use clap::{
     ValueEnum,
     builder::{NonEmptyStringValueParser, PathBufValueParser},
@@ -29,6 +30,12 @@ pub struct Release {
     #[arg(long)]
     registry: Option<String>,
 
+    /// Directory where release-plz publishes the packages instead of a cargo registry.
+    /// Release-plz packages the crates with `cargo package` and adds them to an index
+    /// stored in this directory, so you can rehearse a release without network.
+    #[arg(long, value_parser = PathBufValueParser::new(), conflicts_with_all = ["registry", "token"])]
+    local_registry: Option<PathBuf>,
+
     /// Token used to publish to the cargo registry.
     /// Override the `CARGO_REGISTRY_TOKEN` environment variable, or the `CARGO_REGISTRIES_<NAME>_TOKEN`
     /// environment variable, used for registry specified in the `registry` input variable.
@@ -59,6 +66,21 @@ pub struct Release {
     #[arg(long, value_parser = NonEmptyStringValueParser::new(), env, hide_env_values=true)]
     pub git_token: Option<String>,
 
+    /// Complete the release that a previous run of `release-plz release` didn't finish.
+    /// Release-plz reads the release journal to perform only the missing steps,
+    /// e.g. creating the git release of a package that was already published and tagged.
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Publish the packages even if a `release_freeze` window of the config is active.
+    #[arg(long)]
+    pub ignore_freeze: bool,
+
+    /// Path to the release journal file, where release-plz saves the progress of the release.
+    /// If not provided, release-plz saves it in the `release-plz` directory of the cargo target directory.
+    #[arg(long, value_parser = PathBufValueParser::new())]
+    pub journal_path: Option<PathBuf>,
+
     /// Kind of git forge
     #[arg(long, visible_alias = "backend", value_enum, default_value_t = ReleaseGitForgeKind::Github)]
     forge: ReleaseGitForgeKind,
@@ -84,6 +106,13 @@ pub enum ReleaseGitForgeKind {
 }
 
 impl Release {
+    pub fn secrets(&self) -> Vec<&str> {
+        [self.token.as_deref(), self.git_token.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     pub fn release_request(
         self,
         config: &Config,
@@ -107,7 +136,9 @@ impl Release {
         } else {
             None
         };
-        let mut req = ReleaseRequest::new(metadata).with_dry_run(self.dry_run);
+        let mut req = ReleaseRequest::new(metadata)
+            .with_dry_run(self.dry_run)
+            .with_resume(self.resume);
 
         if let Some(registry) = self.registry {
             req = req.with_registry(registry);
@@ -115,20 +146,52 @@ impl Release {
         if let Some(token) = self.token {
             req = req.with_token(SecretString::from(token));
         }
+        if let Some(local_registry) = self.local_registry {
+            req = req.with_local_registry(to_utf8_pathbuf(local_registry)?);
+        }
         if let Some(repo_url) = self.repo_url {
             req = req.with_repo_url(repo_url);
         }
         if let Some(git_release) = git_release {
             req = req.with_git_release(git_release);
         }
+        if let Some(journal_path) = self.journal_path {
+            req = req.with_journal_path(to_utf8_pathbuf(journal_path)?);
+        }
         if let Some(release_always) = config.workspace.release_always {
             req = req.with_release_always(release_always);
         }
+        req = req.with_gates(config.workspace.release_gates());
+
+        req = req.with_released_prs_config(config.workspace.released_prs_config());
+
+        if let Some(milestone) = config
+            .workspace
+            .milestone_template("milestone_close", config.workspace.milestone_close)?
+        {
+            req = req.with_milestone_to_close(milestone);
+        }
+
+        req = req
+            .with_freeze_windows(config.freeze_windows()?)
+            .with_ignore_freeze(self.ignore_freeze);
 
         req = req.with_publish_timeout(config.workspace.publish_timeout()?);
 
+        req = req.with_publish_concurrency(config.workspace.publish_concurrency()?);
+
+        if let Some(publish_preflight) = config.workspace.publish_preflight {
+            req = req.with_publish_preflight(publish_preflight);
+        }
+
+        if let Some(metadata_lint) = config.workspace.metadata_lint {
+            req = req.with_metadata_lint(metadata_lint);
+        }
+
         req = config.fill_release_config(self.allow_dirty, self.no_verify, req);
 
+        req = config.fill_registries_config(req)?;
+
         req = req.with_branch_prefix(config.workspace.pr_branch_prefix.clone());
 
         req.check_publish_fields()?;
@@ -206,10 +269,14 @@ mod tests {
             no_verify: false,
             manifest_path: None,
             registry: None,
+            local_registry: None,
             token: None,
             dry_run: false,
             repo_url: None,
             git_token: None,
+            resume: false,
+            ignore_freeze: false,
+            journal_path: None,
             forge: ReleaseGitForgeKind::Github,
             config: ConfigPath::default(),
             output: None,
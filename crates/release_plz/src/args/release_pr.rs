@@ -25,13 +25,36 @@ impl ReleasePr {
         let pr_body = config.workspace.pr_body.clone();
         let pr_labels = config.workspace.pr_labels.clone();
         let pr_draft = config.workspace.pr_draft;
+        let metadata_lint = config.workspace.metadata_lint.unwrap_or(true);
         let update_request = self.update.update_request(cargo_metadata)?;
-        let request = ReleasePrRequest::new(update_request)
+        let mut request = ReleasePrRequest::new(update_request)
             .mark_as_draft(pr_draft)
+            .with_metadata_lint(metadata_lint)
             .with_labels(pr_labels)
             .with_branch_prefix(pr_branch_prefix)
             .with_pr_name_template(pr_name)
-            .with_pr_body_template(pr_body);
+            .with_pr_body_template(pr_body)
+            .with_freeze_windows(config.freeze_windows()?)
+            .with_mode(config.workspace.release_pr_mode.unwrap_or_default().into());
+        if let Some(milestone) = config
+            .workspace
+            .milestone_template("pr_milestone", config.workspace.pr_milestone)?
+        {
+            request = request.with_milestone(milestone);
+        }
+        if let Some(merge_method) = config.workspace.pr_auto_merge {
+            request = request.with_auto_merge(merge_method.into());
+        }
+        request = request.with_reviewers(release_plz_core::PrReviewers {
+            reviewers: config.workspace.pr_reviewers.clone(),
+            team_reviewers: config.workspace.pr_team_reviewers.clone(),
+            assignees: config.workspace.pr_assignees.clone(),
+        });
+        for (package, reviewers) in config.package_pr_reviewers() {
+            request = request.with_package_reviewers(package, reviewers);
+        }
+        request =
+            request.with_reviewers_from_codeowners(config.workspace.pr_reviewers_from_codeowners);
         Ok(request)
     }
 }
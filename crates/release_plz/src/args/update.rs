@@ -136,6 +136,10 @@ impl ManifestCommand for Update {
 }
 
 impl Update {
+    pub fn secrets(&self) -> Vec<&str> {
+        self.git_token.as_deref().into_iter().collect()
+    }
+
     pub fn git_forge(&self, repo: RepoUrl) -> anyhow::Result<Option<GitForge>> {
         let Some(token) = self.git_token.clone() else {
             return Ok(None);
@@ -1,13 +1,18 @@
 use anyhow::Context as _;
 use cargo_metadata::camino::Utf8Path;
 use cargo_utils::to_utf8_pathbuf;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use release_plz_core::{
-    GitReleaseConfig, ReleaseRequest, fs_utils::to_utf8_path, set_version::SetVersionRequest,
-    update_request::UpdateRequest,
+    FreezeWindow, GitReleaseConfig, ReleaseRequest, fs_utils::to_utf8_path,
+    set_version::SetVersionRequest, update_request::UpdateRequest,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use url::Url;
 
 use crate::changelog_config::ChangelogCfg;
@@ -29,6 +34,20 @@ pub struct Config {
     /// Not all settings of `workspace` can be overridden.
     #[serde(default)]
     package: Vec<PackageSpecificConfigWithName>,
+    /// # Notification
+    /// Webhooks called when `release` or `release-pr` complete.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    notification: Vec<NotificationConfig>,
+    /// # Registries
+    /// Registry-specific configuration.
+    /// The key is the registry name in the Cargo config. crates.io is `crates-io`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    registries: BTreeMap<String, RegistryCfg>,
+    /// # Release Freeze
+    /// Periods of time when `release` doesn't publish the packages
+    /// and `release-pr` marks the release PR as a draft.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    release_freeze: Vec<ReleaseFreezeConfig>,
 }
 
 impl Config {
@@ -41,6 +60,55 @@ impl Config {
             .collect()
     }
 
+    pub fn notifications(&self) -> Vec<release_plz_core::Notification> {
+        self.notification.iter().cloned().map(Into::into).collect()
+    }
+
+    /// Reviewers and assignees of the release PR, by package name.
+    pub fn package_pr_reviewers(&self) -> Vec<(&str, release_plz_core::PrReviewers)> {
+        self.packages()
+            .into_iter()
+            .map(|(package, config)| (package, config.pr_reviewers()))
+            .filter(|(_, reviewers)| !reviewers.is_empty())
+            .collect()
+    }
+
+    pub fn freeze_windows(&self) -> anyhow::Result<Vec<FreezeWindow>> {
+        self.release_freeze
+            .iter()
+            .enumerate()
+            .map(|(i, window)| {
+                window
+                    .to_freeze_window()
+                    .with_context(|| format!("invalid `release_freeze` window number {}", i + 1))
+            })
+            .collect()
+    }
+
+    /// Make the relative paths of the config relative to the directory of the config file,
+    /// instead of the current directory.
+    pub fn resolve_paths(&mut self, config_dir: &Path) {
+        for registry in self.registries.values_mut() {
+            if let Some(token_file) = &registry.token_file {
+                registry.token_file = Some(config_dir.join(token_file));
+            }
+        }
+    }
+
+    /// Set the token and publish settings of the registries in the release request.
+    pub fn fill_registries_config(
+        &self,
+        mut release_request: ReleaseRequest,
+    ) -> anyhow::Result<ReleaseRequest> {
+        for (registry, config) in &self.registries {
+            let config = config
+                .to_registry_config()
+                .with_context(|| format!("invalid config of registry `{registry}`"))?;
+            release_request = release_request.with_registry_config(registry, config);
+        }
+        Ok(release_request)
+    }
+
     pub fn fill_update_config(
         &self,
         is_changelog_update_disabled: bool,
@@ -109,6 +177,173 @@ impl Config {
     }
 }
 
+/// Config of a `[[notification]]`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// # URL
+    /// URL where release-plz sends the `POST` request.
+    url: Url,
+    /// # Events
+    /// Commands that trigger the notification.
+    /// If empty, all the commands trigger the notification.
+    #[serde(default)]
+    events: Vec<NotificationEvent>,
+    /// # Payload
+    /// Tera template of the JSON payload.
+    /// Defaults to the JSON output of the command.
+    payload: Option<String>,
+    /// # Headers
+    /// Headers of the request.
+    /// The key is the header name, the value is the name of the environment variable
+    /// that contains the header value.
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl From<NotificationConfig> for release_plz_core::Notification {
+    fn from(value: NotificationConfig) -> Self {
+        Self::new(value.url)
+            .with_events(value.events.into_iter().map(Into::into).collect())
+            .with_payload_template(value.payload)
+            .with_headers(value.headers)
+    }
+}
+
+/// Config of a `[[release_freeze]]`.
+/// Set either `start` and `end`, or `cron` and `duration`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseFreezeConfig {
+    /// # Start
+    /// Start of the freeze, e.g. `2024-12-20T00:00:00Z` or `2024-12-20`.
+    /// A date without time means midnight UTC.
+    start: Option<String>,
+    /// # End
+    /// End of the freeze, e.g. `2025-01-06T08:00:00Z` or `2025-01-05`.
+    /// A date without time means the end of that day (UTC).
+    end: Option<String>,
+    /// # Cron
+    /// Cron expression of the start of a recurring freeze, evaluated in UTC.
+    /// E.g. `0 16 * * FRI` freezes the releases every Friday at 16:00.
+    cron: Option<String>,
+    /// # Duration
+    /// Duration of a recurring freeze, e.g. `64h`.
+    duration: Option<String>,
+    /// # Reason
+    /// Why the releases are frozen. Shown in the release PR and in the `release` error.
+    reason: Option<String>,
+}
+
+impl ReleaseFreezeConfig {
+    fn to_freeze_window(&self) -> anyhow::Result<FreezeWindow> {
+        let window = match (&self.start, &self.end, &self.cron, &self.duration) {
+            (Some(start), Some(end), None, None) => {
+                let start = parse_freeze_time(start, false).context("invalid `start`")?;
+                let end = parse_freeze_time(end, true).context("invalid `end`")?;
+                FreezeWindow::range(start, end)?
+            }
+            (None, None, Some(cron), Some(duration)) => {
+                let duration = parse_duration(duration).context("invalid `duration`")?;
+                FreezeWindow::recurring(cron, duration)?
+            }
+            _ => anyhow::bail!("set either `start` and `end`, or `cron` and `duration`"),
+        };
+        Ok(match &self.reason {
+            Some(reason) => window.with_reason(reason),
+            None => window,
+        })
+    }
+}
+
+/// Parse an RFC 3339 date time or a `YYYY-MM-DD` date.
+/// If `end_of_day` is true, a date means the midnight after that day.
+fn parse_freeze_time(input: &str, end_of_day: bool) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time.to_utc());
+    }
+    let date = NaiveDate::parse_from_str(input, "%Y-%m-%d").with_context(|| {
+        format!("`{input}` is neither a RFC 3339 date time nor a YYYY-MM-DD date")
+    })?;
+    let date = if end_of_day {
+        date.succ_opt().context("date out of range")?
+    } else {
+        date
+    };
+    Ok(date.and_time(NaiveTime::MIN).and_utc())
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationEvent {
+    /// # Release
+    /// `release-plz release` released at least one package.
+    Release,
+    /// # Release PR
+    /// `release-plz release-pr` opened or updated the release PR.
+    ReleasePr,
+}
+
+impl From<NotificationEvent> for release_plz_core::NotificationEvent {
+    fn from(value: NotificationEvent) -> Self {
+        match value {
+            NotificationEvent::Release => Self::Release,
+            NotificationEvent::ReleasePr => Self::ReleasePr,
+        }
+    }
+}
+
+/// Config of a `[registries.<name>]` section.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryCfg {
+    /// # Token Env
+    /// Name of the environment variable containing the registry token.
+    pub token_env: Option<String>,
+    /// # Token File
+    /// File containing the registry token.
+    pub token_file: Option<PathBuf>,
+    /// # Publish Timeout
+    /// Timeout for the publishing process in this registry.
+    /// Defaults to the `publish_timeout` of the `[workspace]` section.
+    pub publish_timeout: Option<String>,
+    /// # Publish No Verify
+    /// If `true`, don't verify the contents by building them when publishing to this registry.
+    /// Defaults to the `publish_no_verify` of the package.
+    pub publish_no_verify: Option<bool>,
+}
+
+impl RegistryCfg {
+    fn to_registry_config(&self) -> anyhow::Result<release_plz_core::RegistryConfig> {
+        let mut config = release_plz_core::RegistryConfig::default();
+        match (&self.token_env, &self.token_file) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("`token_env` and `token_file` can't be set at the same time")
+            }
+            (Some(token_env), None) => {
+                config = config.with_token(release_plz_core::RegistryTokenSource::Env(
+                    token_env.clone(),
+                ));
+            }
+            (None, Some(token_file)) => {
+                config = config.with_token(release_plz_core::RegistryTokenSource::File(
+                    to_utf8_pathbuf(token_file.clone())?,
+                ));
+            }
+            (None, None) => {}
+        }
+        if let Some(publish_timeout) = &self.publish_timeout {
+            let publish_timeout = parse_duration(publish_timeout)
+                .with_context(|| format!("invalid publish_timeout '{publish_timeout}'"))?;
+            config = config.with_publish_timeout(publish_timeout);
+        }
+        if let Some(no_verify) = self.publish_no_verify {
+            config = config.with_no_verify(no_verify);
+        }
+        Ok(config)
+    }
+}
+
 /// Config at the `[workspace]` level.
 #[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -141,12 +376,49 @@ pub struct Workspace {
     /// Labels to add to the release PR.
     #[serde(default)]
     pub pr_labels: Vec<String>,
+    /// # PR Reviewers
+    /// Users to request a review of the release PR from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pr_reviewers: Vec<String>,
+    /// # PR Team Reviewers
+    /// Teams to request a review of the release PR from.
+    /// Not supported by GitLab.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pr_team_reviewers: Vec<String>,
+    /// # PR Assignees
+    /// Users to assign the release PR to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pr_assignees: Vec<String>,
+    /// # PR Reviewers From Codeowners
+    /// If `true`, request a review of the release PR from the owners of the updated packages,
+    /// according to the `CODEOWNERS` file of the repository.
+    #[serde(default)]
+    pub pr_reviewers_from_codeowners: bool,
     /// # PR Branch Prefix
     /// Prefix for the PR Branch
     pub pr_branch_prefix: Option<String>,
+    /// # PR Auto Merge
+    /// If set, enable auto-merge for the release PR with this merge method.
+    pub pr_auto_merge: Option<MergeMethod>,
     /// # Publish Timeout
     /// Timeout for the publishing process
     pub publish_timeout: Option<String>,
+    /// # Publish Concurrency
+    /// Maximum number of packages published at the same time.
+    /// Only packages that don't depend on each other are published concurrently.
+    /// Defaults to 1.
+    pub publish_concurrency: Option<usize>,
+    /// # Publish Preflight
+    /// - If `true`, before publishing any package, release-plz packages and verifies all the
+    ///   packages to publish and checks their mandatory fields.
+    ///   If any package fails, nothing is published.
+    /// - If `false` or [`Option::None`], packages are verified one by one, while publishing them.
+    pub publish_preflight: Option<bool>,
+    /// # Metadata Lint
+    /// - If `true` or [`Option::None`], `release` and `release-pr` check that the `Cargo.toml`
+    ///   metadata of the packages to release is accepted by crates.io. *(Default)*.
+    /// - If `false`, the metadata isn't checked.
+    pub metadata_lint: Option<bool>,
     /// # Repo URL
     /// GitHub/Gitea/GitLab repository url where your project is hosted.
     /// It is used to generate the changelog release link.
@@ -155,6 +427,9 @@ pub struct Workspace {
     /// # Release Commits
     /// Prepare release only if at least one commit respects this regex.
     pub release_commits: Option<String>,
+    /// # Release PR Mode
+    /// How `release-plz release-pr` splits the packages to release in release PRs.
+    pub release_pr_mode: Option<ReleasePrMode>,
     /// # Release always
     /// - If true, release-plz release will try to release your packages every time you run it
     ///   (e.g. on every commit in the main branch). *(Default)*.
@@ -165,15 +440,93 @@ pub struct Workspace {
     ///   `release-plz-`. So if you want to create a PR that should trigger a release
     ///   (e.g. when you fix the CI), use this branch name format (e.g. `release-plz-fix-ci`).
     pub release_always: Option<bool>,
+    /// # Release Required Labels
+    /// Labels that the merged release PR must have.
+    /// If the PR doesn't have them, `release-plz release` doesn't publish the packages.
+    pub release_required_labels: Option<Vec<String>>,
+    /// # Release Required Approvals
+    /// Minimum number of users that approved the merged release PR.
+    /// If the PR has fewer approvals, `release-plz release` doesn't publish the packages.
+    pub release_required_approvals: Option<u32>,
+    /// # Release Required Status
+    /// If `true`, the statuses and checks of the last commit of the merged release PR must be
+    /// successful. Otherwise, `release-plz release` doesn't publish the packages.
+    pub release_required_status: Option<bool>,
+    /// # Released PR Comment
+    /// Tera template of the comment that `release-plz release` posts on the PRs included in
+    /// the release and on the issues they close. If unspecified, release-plz doesn't comment.
+    pub released_pr_comment: Option<String>,
+    /// # Released PR Label
+    /// Label that `release-plz release` adds to the PRs included in the release
+    /// and to the issues they close.
+    pub released_pr_label: Option<String>,
+    /// # Milestone
+    /// Tera template of the title of the milestone of a package version,
+    /// e.g. `{{ package }} v{{ version }}`.
+    /// Used by `milestone_close` and `pr_milestone`.
+    pub milestone: Option<String>,
+    /// # Milestone Close
+    /// If `true`, `release-plz release` closes the milestone of the released version.
+    pub milestone_close: Option<bool>,
+    /// # PR Milestone
+    /// If `true`, `release-plz release-pr` assigns the release PR to the milestone of the
+    /// upcoming version, creating the milestone if it doesn't exist.
+    pub pr_milestone: Option<bool>,
 }
 
 impl Workspace {
+    /// Conditions that the merged release PR must meet to publish the packages.
+    pub fn release_gates(&self) -> release_plz_core::ReleaseGates {
+        release_plz_core::ReleaseGates::default()
+            .with_labels(self.release_required_labels.clone().unwrap_or_default())
+            .with_approvals(self.release_required_approvals.unwrap_or_default())
+            .with_passing_status(self.release_required_status == Some(true))
+    }
+
+    /// Template of the milestone title, if the `enabled` field is `true`.
+    pub fn milestone_template(
+        &self,
+        field: &str,
+        enabled: Option<bool>,
+    ) -> anyhow::Result<Option<&str>> {
+        if enabled != Some(true) {
+            return Ok(None);
+        }
+        let template = self
+            .milestone
+            .as_deref()
+            .with_context(|| format!("`{field}` is enabled, but `milestone` isn't set"))?;
+        Ok(Some(template))
+    }
+
+    /// How to update the PRs and issues included in the release.
+    pub fn released_prs_config(&self) -> release_plz_core::ReleasedPrsConfig {
+        let mut config = release_plz_core::ReleasedPrsConfig::default();
+        if let Some(comment) = &self.released_pr_comment {
+            config = config.with_comment_template(comment);
+        }
+        if let Some(label) = &self.released_pr_label {
+            config = config.with_label(label);
+        }
+        config
+    }
+
     /// Get the publish timeout. Defaults to 30 minutes.
     pub fn publish_timeout(&self) -> anyhow::Result<Duration> {
         let publish_timeout = self.publish_timeout.as_deref().unwrap_or("30m");
         parse_duration(publish_timeout)
             .with_context(|| format!("invalid publish_timeout '{publish_timeout}'"))
     }
+
+    /// Get the maximum number of packages to publish at the same time. Defaults to 1.
+    pub fn publish_concurrency(&self) -> anyhow::Result<usize> {
+        let publish_concurrency = self.publish_concurrency.unwrap_or(1);
+        anyhow::ensure!(
+            publish_concurrency > 0,
+            "invalid publish_concurrency '{publish_concurrency}': it must be greater than 0"
+        );
+        Ok(publish_concurrency)
+    }
 }
 
 /// Parse the duration from the input string.
@@ -233,6 +586,18 @@ pub struct PackageSpecificConfig {
     /// # Version group
     /// The name of a group of packages that needs to have the same version.
     version_group: Option<String>,
+    /// # PR Reviewers
+    /// Users to request a review of the release PR from, when it updates this package.
+    /// They are added to the `pr_reviewers` of the `[workspace]`.
+    pr_reviewers: Option<Vec<String>>,
+    /// # PR Team Reviewers
+    /// Teams to request a review of the release PR from, when it updates this package.
+    /// They are added to the `pr_team_reviewers` of the `[workspace]`.
+    pr_team_reviewers: Option<Vec<String>>,
+    /// # PR Assignees
+    /// Users to assign the release PR to, when it updates this package.
+    /// They are added to the `pr_assignees` of the `[workspace]`.
+    pr_assignees: Option<Vec<String>>,
 }
 
 impl PackageSpecificConfig {
@@ -242,6 +607,17 @@ impl PackageSpecificConfig {
             common: self.common.merge(default),
             changelog_include: self.changelog_include,
             version_group: self.version_group,
+            pr_reviewers: self.pr_reviewers,
+            pr_team_reviewers: self.pr_team_reviewers,
+            pr_assignees: self.pr_assignees,
+        }
+    }
+
+    fn pr_reviewers(&self) -> release_plz_core::PrReviewers {
+        release_plz_core::PrReviewers {
+            reviewers: self.pr_reviewers.clone().unwrap_or_default(),
+            team_reviewers: self.pr_team_reviewers.clone().unwrap_or_default(),
+            assignees: self.pr_assignees.clone().unwrap_or_default(),
         }
     }
 }
@@ -256,17 +632,27 @@ pub struct PackageSpecificConfigWithName {
 impl From<PackageConfig> for release_plz_core::ReleaseConfig {
     fn from(value: PackageConfig) -> Self {
         let is_publish_enabled = value.publish != Some(false);
+        let is_verify_upload_enabled = value.publish_verify_upload == Some(true);
         let is_git_tag_enabled = value.git_tag_enable != Some(false);
         let git_tag_name = value.git_tag_name.clone();
+        let git_tag_message = value.git_tag_message.clone();
+        let is_git_tag_sign_enabled = value.git_tag_sign == Some(true);
         let release = value.release != Some(false);
         let mut cfg = Self::default()
-            .with_publish(release_plz_core::PublishConfig::enabled(is_publish_enabled))
+            .with_publish(
+                release_plz_core::PublishConfig::enabled(is_publish_enabled)
+                    .with_verify_upload(is_verify_upload_enabled)
+                    .with_contents_limits(package_contents_limits(&value)),
+            )
             .with_git_release(git_release(&value))
             .with_git_tag(
                 release_plz_core::GitTagConfig::enabled(is_git_tag_enabled)
-                    .set_name_template(git_tag_name),
+                    .set_name_template(git_tag_name)
+                    .set_message_template(git_tag_message)
+                    .set_sign(is_git_tag_sign_enabled),
             )
-            .with_release(release);
+            .with_release(release)
+            .with_hooks(hooks(&value));
 
         if let Some(changelog_update) = value.changelog_update {
             cfg = cfg.with_changelog_update(changelog_update);
@@ -290,6 +676,23 @@ impl From<PackageConfig> for release_plz_core::ReleaseConfig {
     }
 }
 
+fn package_contents_limits(config: &PackageConfig) -> release_plz_core::PackageContentsLimits {
+    let mut limits = release_plz_core::PackageContentsLimits::default()
+        .with_forbidden_files(config.forbidden_package_files.clone().unwrap_or_default());
+    if let Some(max_package_size) = config.max_package_size {
+        limits = limits.with_max_size(max_package_size);
+    }
+    limits
+}
+
+fn hooks(config: &PackageConfig) -> release_plz_core::Hooks {
+    release_plz_core::Hooks::default()
+        .with_pre_publish(config.pre_publish.clone().unwrap_or_default())
+        .with_post_publish(config.post_publish.clone().unwrap_or_default())
+        .with_post_tag(config.post_tag.clone().unwrap_or_default())
+        .with_post_release(config.post_release.clone().unwrap_or_default())
+}
+
 fn git_release(config: &PackageConfig) -> GitReleaseConfig {
     let is_git_release_enabled = config.git_release_enable != Some(false);
     let git_release_type: release_plz_core::ReleaseType = config
@@ -299,11 +702,18 @@ fn git_release(config: &PackageConfig) -> GitReleaseConfig {
     let is_git_release_draft = config.git_release_draft == Some(true);
     let git_release_name = config.git_release_name.clone();
     let git_release_body = config.git_release_body.clone();
+    let git_release_assets = config.git_release_assets.clone().unwrap_or_default();
+    let git_release_if_exists: release_plz_core::ExistingReleasePolicy = config
+        .git_release_if_exists
+        .map(|if_exists| if_exists.into())
+        .unwrap_or_default();
     let mut git_release = release_plz_core::GitReleaseConfig::enabled(is_git_release_enabled)
         .set_draft(is_git_release_draft)
         .set_release_type(git_release_type)
         .set_name_template(git_release_name)
-        .set_body_template(git_release_body);
+        .set_body_template(git_release_body)
+        .set_assets(git_release_assets)
+        .set_if_exists(git_release_if_exists);
 
     if config.git_release_latest == Some(false) {
         git_release = git_release.set_latest(false);
@@ -333,9 +743,17 @@ pub struct PackageConfig {
     /// Publish the GitHub/Gitea/GitLab release for the created git tag.
     /// Enabled by default.
     pub git_release_enable: Option<bool>,
+    /// # Git Release Assets
+    /// Glob patterns of the files to upload to the GitHub/Gitea/GitLab release.
+    /// The patterns are tera templates. Relative patterns start from the workspace root.
+    pub git_release_assets: Option<Vec<String>>,
     /// # Git Release Body
     /// Tera template of the git release body created by release-plz.
     pub git_release_body: Option<String>,
+    /// # Git Release If Exists
+    /// What to do if the git release already exists.
+    /// If unspecified, release-plz fails.
+    pub git_release_if_exists: Option<ExistingReleasePolicy>,
     /// # Git Release Type
     /// Whether to mark the created release as not ready for production.
     pub git_release_type: Option<ReleaseType>,
@@ -355,6 +773,13 @@ pub struct PackageConfig {
     /// # Git Tag Name
     /// Tera template of the git tag name created by release-plz.
     pub git_tag_name: Option<String>,
+    /// # Git Tag Message
+    /// Tera template of the message of the git tag created by release-plz.
+    pub git_tag_message: Option<String>,
+    /// # Git Tag Sign
+    /// If `true`, sign the git tag with the key configured in git.
+    /// Release-plz fails if the created tag isn't signed.
+    pub git_tag_sign: Option<bool>,
     /// # Publish
     /// If `false`, don't run `cargo publish`.
     pub publish: Option<bool>,
@@ -370,6 +795,21 @@ pub struct PackageConfig {
     /// # Publish All Features
     /// If `true`, add the `--all-features` flag to the `cargo publish` command.
     pub publish_all_features: Option<bool>,
+    /// # Publish Verify Upload
+    /// If `true`, after `cargo publish`, download the package from the registry and check
+    /// that it contains the same files as the local package and that it was built from the
+    /// released commit.
+    /// If the check fails, release-plz doesn't create the git release.
+    pub publish_verify_upload: Option<bool>,
+    /// # Max Package Size
+    /// Maximum size in bytes of the files of the package, before compression.
+    /// If a package is bigger, release-plz doesn't publish any package.
+    pub max_package_size: Option<u64>,
+    /// # Forbidden Package Files
+    /// Glob patterns of the files that the package must not contain.
+    /// The patterns are relative to the package root.
+    /// If a package contains one of these files, release-plz doesn't publish any package.
+    pub forbidden_package_files: Option<Vec<String>>,
     /// # Semver Check
     /// Controls when to run cargo-semver-checks.
     /// If unspecified, run cargo-semver-checks if the package is a library.
@@ -377,6 +817,19 @@ pub struct PackageConfig {
     /// # Release
     /// Used to toggle off the update/release process for a workspace or package.
     pub release: Option<bool>,
+    /// # Pre Publish
+    /// Commands to run before `cargo publish`.
+    /// If a command fails, the package isn't released.
+    pub pre_publish: Option<Vec<String>>,
+    /// # Post Publish
+    /// Commands to run after `cargo publish`.
+    pub post_publish: Option<Vec<String>>,
+    /// # Post Tag
+    /// Commands to run after pushing the git tag.
+    pub post_tag: Option<Vec<String>>,
+    /// # Post Release
+    /// Commands to run after creating the git release.
+    pub post_release: Option<Vec<String>>,
 }
 
 impl From<PackageConfig> for release_plz_core::UpdateConfig {
@@ -413,20 +866,33 @@ impl PackageConfig {
                 .features_always_increment_minor
                 .or(default.features_always_increment_minor),
             git_release_enable: self.git_release_enable.or(default.git_release_enable),
+            git_release_assets: self.git_release_assets.or(default.git_release_assets),
             git_release_type: self.git_release_type.or(default.git_release_type),
             git_release_draft: self.git_release_draft.or(default.git_release_draft),
             git_release_latest: self.git_release_latest.or(default.git_release_latest),
             git_release_name: self.git_release_name.or(default.git_release_name),
             git_release_body: self.git_release_body.or(default.git_release_body),
+            git_release_if_exists: self.git_release_if_exists.or(default.git_release_if_exists),
 
             publish: self.publish.or(default.publish),
             publish_allow_dirty: self.publish_allow_dirty.or(default.publish_allow_dirty),
             publish_no_verify: self.publish_no_verify.or(default.publish_no_verify),
             publish_features: self.publish_features.or(default.publish_features),
             publish_all_features: self.publish_all_features.or(default.publish_all_features),
+            publish_verify_upload: self.publish_verify_upload.or(default.publish_verify_upload),
+            max_package_size: self.max_package_size.or(default.max_package_size),
+            forbidden_package_files: self
+                .forbidden_package_files
+                .or(default.forbidden_package_files),
             git_tag_enable: self.git_tag_enable.or(default.git_tag_enable),
             git_tag_name: self.git_tag_name.or(default.git_tag_name),
+            git_tag_message: self.git_tag_message.or(default.git_tag_message),
+            git_tag_sign: self.git_tag_sign.or(default.git_tag_sign),
             release: self.release.or(default.release),
+            pre_publish: self.pre_publish.or(default.pre_publish),
+            post_publish: self.post_publish.or(default.post_publish),
+            post_tag: self.post_tag.or(default.post_tag),
+            post_release: self.post_release.or(default.post_release),
         }
     }
 
@@ -477,6 +943,83 @@ impl From<ReleaseType> for release_plz_core::ReleaseType {
     }
 }
 
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingReleasePolicy {
+    /// # Skip
+    /// Leave the existing release as it is.
+    Skip,
+    /// # Update
+    /// Update name, body, draft, pre-release and latest flags of the existing release.
+    /// Upload the assets that are missing in the release.
+    Update,
+    /// # Fail
+    /// Return an error.
+    #[default]
+    Fail,
+}
+
+impl From<ExistingReleasePolicy> for release_plz_core::ExistingReleasePolicy {
+    fn from(value: ExistingReleasePolicy) -> Self {
+        match value {
+            ExistingReleasePolicy::Skip => Self::Skip,
+            ExistingReleasePolicy::Update => Self::Update,
+            ExistingReleasePolicy::Fail => Self::Fail,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleasePrMode {
+    /// # Workspace
+    /// One release PR for all the packages.
+    #[default]
+    Workspace,
+    /// # Package
+    /// One release PR for each package.
+    /// Packages of the same version group share the release PR.
+    Package,
+    /// # Version Group
+    /// One release PR for each version group.
+    /// Packages without a version group share one release PR.
+    VersionGroup,
+}
+
+impl From<ReleasePrMode> for release_plz_core::ReleasePrMode {
+    fn from(value: ReleasePrMode) -> Self {
+        match value {
+            ReleasePrMode::Workspace => Self::Workspace,
+            ReleasePrMode::Package => Self::Package,
+            ReleasePrMode::VersionGroup => Self::VersionGroup,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMethod {
+    /// # Merge
+    /// Add all the commits of the PR to the base branch with a merge commit.
+    Merge,
+    /// # Squash
+    /// Combine the commits of the PR into one commit.
+    Squash,
+    /// # Rebase
+    /// Add all the commits of the PR to the base branch, without a merge commit.
+    Rebase,
+}
+
+impl From<MergeMethod> for release_plz_core::MergeMethod {
+    fn from(value: MergeMethod) -> Self {
+        match value {
+            MergeMethod::Merge => Self::Merge,
+            MergeMethod::Squash => Self::Squash,
+            MergeMethod::Rebase => Self::Rebase,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -526,10 +1069,30 @@ mod tests {
                 pr_labels: vec![],
                 pr_branch_prefix: Some("f-".to_string()),
                 publish_timeout: Some("10m".to_string()),
+                publish_concurrency: None,
+                publish_preflight: None,
+                metadata_lint: None,
                 release_commits: Some("^feat:".to_string()),
+                release_pr_mode: None,
                 release_always: None,
+                release_required_labels: None,
+                release_required_approvals: None,
+                release_required_status: None,
+                released_pr_comment: None,
+                released_pr_label: None,
+                milestone: None,
+                milestone_close: None,
+                pr_milestone: None,
+                pr_auto_merge: None,
+                pr_reviewers: vec![],
+                pr_team_reviewers: vec![],
+                pr_assignees: vec![],
+                pr_reviewers_from_codeowners: false,
             },
             package: [].into(),
+            notification: vec![],
+            registries: BTreeMap::new(),
+            release_freeze: vec![],
         }
     }
 
@@ -547,6 +1110,9 @@ mod tests {
                 },
                 changelog_include: None,
                 version_group: None,
+                pr_reviewers: None,
+                pr_team_reviewers: None,
+                pr_assignees: None,
             },
         }
     }
@@ -650,8 +1216,25 @@ mod tests {
                     ..Default::default()
                 },
                 publish_timeout: Some("10m".to_string()),
+                publish_concurrency: None,
+                publish_preflight: None,
+                metadata_lint: None,
                 release_commits: Some("^feat:".to_string()),
+                release_pr_mode: None,
                 release_always: None,
+                release_required_labels: None,
+                release_required_approvals: None,
+                release_required_status: None,
+                released_pr_comment: None,
+                released_pr_label: None,
+                milestone: None,
+                milestone_close: None,
+                pr_milestone: None,
+                pr_auto_merge: None,
+                pr_reviewers: vec![],
+                pr_team_reviewers: vec![],
+                pr_assignees: vec![],
+                pr_reviewers_from_codeowners: false,
             },
             package: [PackageSpecificConfigWithName {
                 name: "crate1".to_string(),
@@ -667,9 +1250,15 @@ mod tests {
                     },
                     changelog_include: Some(vec!["pkg1".to_string()]),
                     version_group: None,
+                    pr_reviewers: None,
+                    pr_team_reviewers: None,
+                    pr_assignees: None,
                 },
             }]
             .into(),
+            notification: vec![],
+            registries: BTreeMap::new(),
+            release_freeze: vec![],
         };
 
         expect_test::expect![[r#"
@@ -683,6 +1272,7 @@ mod tests {
             changelog_config = "../git-cliff.toml"
             pr_draft = false
             pr_labels = ["label1"]
+            pr_reviewers_from_codeowners = false
             pr_branch_prefix = "f-"
             publish_timeout = "10m"
             repo_url = "https://github.com/release-plz/release-plz"
@@ -703,6 +1293,122 @@ mod tests {
         .assert_eq(&toml::to_string(&config).unwrap());
     }
 
+    #[test]
+    fn notification_is_deserialized() {
+        let config = r#"
+            [[notification]]
+            url = "https://example.com/hook"
+            events = ["release-pr"]
+            headers = { Authorization = "HOOK_TOKEN" }
+        "#;
+
+        let config: Config = toml::from_str(config).unwrap();
+        let expected_notification =
+            release_plz_core::Notification::new("https://example.com/hook".parse().unwrap())
+                .with_events(vec![release_plz_core::NotificationEvent::ReleasePr])
+                .with_headers(BTreeMap::from([(
+                    "Authorization".to_string(),
+                    "HOOK_TOKEN".to_string(),
+                )]));
+        assert_eq!(config.notifications(), vec![expected_notification]);
+    }
+
+    #[test]
+    fn release_freeze_is_deserialized() {
+        let config = r#"
+            [[release_freeze]]
+            start = "2024-12-20"
+            end = "2025-01-05"
+            reason = "holidays"
+
+            [[release_freeze]]
+            cron = "0 16 * * FRI"
+            duration = "64h"
+        "#;
+
+        let config: Config = toml::from_str(config).unwrap();
+        let expected_windows = vec![
+            FreezeWindow::range(
+                "2024-12-20T00:00:00Z".parse().unwrap(),
+                "2025-01-06T00:00:00Z".parse().unwrap(),
+            )
+            .unwrap()
+            .with_reason("holidays"),
+            FreezeWindow::recurring("0 16 * * FRI", Duration::from_secs(64 * 60 * 60)).unwrap(),
+        ];
+        assert_eq!(config.freeze_windows().unwrap(), expected_windows);
+
+        let config: Config =
+            toml::from_str("[[release_freeze]]\nstart = \"2024-12-20\"\nduration = \"1h\"")
+                .unwrap();
+        assert_eq!(
+            format!("{:#}", config.freeze_windows().unwrap_err()),
+            "invalid `release_freeze` window number 1: set either `start` and `end`, or `cron` and `duration`"
+        );
+    }
+
+    #[test]
+    fn registries_are_deserialized() {
+        let config = r#"
+            [registries.my-registry]
+            token_env = "MY_REGISTRY_TOKEN"
+            publish_timeout = "5m"
+            publish_no_verify = true
+        "#;
+
+        let config: Config = toml::from_str(config).unwrap();
+        let registry_config = config.registries["my-registry"]
+            .to_registry_config()
+            .unwrap();
+        let expected = release_plz_core::RegistryConfig::default()
+            .with_token(release_plz_core::RegistryTokenSource::Env(
+                "MY_REGISTRY_TOKEN".to_string(),
+            ))
+            .with_publish_timeout(Duration::from_secs(5 * 60))
+            .with_no_verify(true);
+        assert_eq!(registry_config, expected);
+    }
+
+    #[test]
+    fn relative_token_file_is_relative_to_the_config_dir() {
+        let config = r#"
+            [registries.relative]
+            token_file = "secrets/token.txt"
+
+            [registries.absolute]
+            token_file = "/run/secrets/token.txt"
+        "#;
+
+        let mut config: Config = toml::from_str(config).unwrap();
+        config.resolve_paths(Path::new("/repo/config"));
+        assert_eq!(
+            config.registries["relative"].token_file,
+            Some(PathBuf::from("/repo/config/secrets/token.txt"))
+        );
+        assert_eq!(
+            config.registries["absolute"].token_file,
+            Some(PathBuf::from("/run/secrets/token.txt"))
+        );
+    }
+
+    #[test]
+    fn registry_with_two_token_sources_is_rejected() {
+        let config = r#"
+            [registries.my-registry]
+            token_env = "MY_REGISTRY_TOKEN"
+            token_file = "token.txt"
+        "#;
+
+        let config: Config = toml::from_str(config).unwrap();
+        let error = config
+            .fill_registries_config(ReleaseRequest::new(fake_package::metadata::fake_metadata()))
+            .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid config of registry `my-registry`: `token_env` and `token_file` can't be set at the same time"
+        );
+    }
+
     #[test]
     fn wrong_config_section_is_not_deserialized() {
         let config = "[unknown]";
@@ -713,7 +1419,7 @@ mod tests {
               |
             1 | [unknown]
               |  ^^^^^^^
-            unknown field `unknown`, expected one of `workspace`, `changelog`, `package`
+            unknown field `unknown`, expected one of `workspace`, `changelog`, `package`, `notification`, `registries`, `release_freeze`
         "#]]
         .assert_eq(&error);
     }
@@ -791,4 +1497,17 @@ unknown = false"#;
             "invalid duration number"
         );
     }
+
+    #[test]
+    fn publish_concurrency_must_be_greater_than_zero() {
+        let config: Config = toml::from_str("[workspace]\npublish_concurrency = 0").unwrap();
+        assert_eq!(
+            config
+                .workspace
+                .publish_concurrency()
+                .unwrap_err()
+                .to_string(),
+            "invalid publish_concurrency '0': it must be greater than 0"
+        );
+    }
 }
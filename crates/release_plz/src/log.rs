@@ -1,6 +1,9 @@
+use std::io::{self, Write};
+
 use tracing::{Level, level_filters::LevelFilter};
 use tracing_subscriber::{
-    EnvFilter, filter::filter_fn, fmt, layer::SubscriberExt, util::SubscriberInitExt,
+    EnvFilter, filter::filter_fn, fmt, fmt::MakeWriter, layer::SubscriberExt,
+    util::SubscriberInitExt,
 };
 
 /// Intialize the logging using the tracing crate.
@@ -13,6 +16,8 @@ use tracing_subscriber::{
 ///
 /// To maximize logs readability in CI, logs are written in one line
 /// (we don't split them in multiple lines).
+///
+/// The secrets registered with [`release_plz_core::add_secret`] are redacted from the logs.
 pub fn init(verbosity: Option<LevelFilter>) {
     let env_filter = EnvFilter::try_from_env("RELEASE_PLZ_LOG").unwrap_or_else(|_| {
         EnvFilter::builder()
@@ -30,7 +35,7 @@ pub fn init(verbosity: Option<LevelFilter>) {
 
     fmt()
         .with_env_filter(env_filter)
-        .with_writer(std::io::stderr)
+        .with_writer(RedactingMakeWriter(io::stderr))
         .with_target(verbose)
         .with_file(verbose)
         .with_line_number(verbose)
@@ -38,3 +43,93 @@ pub fn init(verbosity: Option<LevelFilter>) {
         .with(ignore_info_spans)
         .init();
 }
+
+/// Creates writers that replace the known secrets with [`release_plz_core::REDACTED`].
+struct RedactingMakeWriter<M>(M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The formatter writes each log line with a single call,
+        // so a secret is never split across two calls.
+        let text = String::from_utf8_lossy(buf);
+        let redacted = release_plz_core::redact_secrets(&text);
+        self.0.write_all(redacted.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Context as _;
+    use tracing::{debug, error, trace};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn secrets_are_not_logged_at_trace_level() {
+        let registry_token = "cio_registry_token_for_log_test";
+        let git_token = "ghp_git_token_for_log_test";
+        release_plz_core::add_secret(registry_token);
+        release_plz_core::add_secret(git_token);
+
+        let buffer = Buffer::default();
+        let make_writer = {
+            let buffer = buffer.clone();
+            RedactingMakeWriter(move || buffer.clone())
+        };
+        // Same configuration as `-vvv`.
+        let subscriber = fmt()
+            .with_max_level(LevelFilter::TRACE)
+            .with_writer(make_writer)
+            .with_target(true)
+            .with_file(true)
+            .with_line_number(true)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            trace!("token: {registry_token}");
+            debug!("cargo stderr: Authorization: Bearer {git_token}");
+            let error = Err::<(), _>(anyhow::anyhow!("invalid token {registry_token}"))
+                .context(format!("request with {git_token} failed"))
+                .unwrap_err();
+            error!("{error:?}");
+        });
+
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(!logs.contains(registry_token), "{logs}");
+        assert!(!logs.contains(git_token), "{logs}");
+        assert_eq!(
+            logs.matches(release_plz_core::REDACTED).count(),
+            4,
+            "{logs}"
+        );
+    }
+}
@@ -8,7 +8,7 @@ mod update_checker;
 
 use args::OutputType;
 use clap::Parser;
-use release_plz_core::ReleaseRequest;
+use release_plz_core::{NotificationEvent, ReleaseRequest};
 use serde::Serialize;
 use tracing::error;
 
@@ -18,6 +18,9 @@ use crate::args::{CliArgs, Command, manifest_command::ManifestCommand as _};
 async fn main() -> anyhow::Result<()> {
     let args = CliArgs::parse();
     log::init(args.verbosity()?);
+    for secret in args.secrets() {
+        release_plz_core::add_secret(secret);
+    }
     run(args).await.map_err(|e| {
         error!("{:?}", e);
         e
@@ -42,28 +45,46 @@ async fn run(args: CliArgs) -> anyhow::Result<()> {
             let cargo_metadata = cmd_args.update.cargo_metadata()?;
             let config = cmd_args.update.config.load()?;
             let request = cmd_args.release_pr_req(&config, cargo_metadata)?;
-            let release_pr = release_plz_core::release_pr(&request).await?;
+            let prs = release_plz_core::release_pr(&request).await?;
+            let is_pr_updated = !prs.is_empty();
+            let prs_json = serde_json::json!({
+                "prs": prs
+            });
             if let Some(output_type) = cmd_args.output {
-                let prs = match release_pr {
-                    Some(pr) => vec![pr],
-                    None => vec![],
-                };
-                let prs_json = serde_json::json!({
-                    "prs": prs
-                });
-                print_output(output_type, prs_json);
+                print_output(output_type, &prs_json);
+            }
+            if is_pr_updated {
+                release_plz_core::send_notifications(
+                    &config.notifications(),
+                    NotificationEvent::ReleasePr,
+                    &prs_json,
+                )
+                .await?;
             }
         }
         Command::Release(cmd_args) => {
             let cargo_metadata = cmd_args.cargo_metadata()?;
             let config = cmd_args.config.load()?;
             let cmd_args_output = cmd_args.output;
+            let dry_run = cmd_args.dry_run;
             let request: ReleaseRequest = cmd_args.release_request(&config, cargo_metadata)?;
-            let output = release_plz_core::release(&request)
-                .await?
-                .unwrap_or_default();
+            let release = release_plz_core::release(&request).await?;
+            // Don't notify about releases that didn't happen.
+            let should_notify = release
+                .as_ref()
+                .is_some_and(|release| release.skipped_reason().is_none())
+                && !dry_run;
+            let output = release.unwrap_or_default();
             if let Some(output_type) = cmd_args_output {
-                print_output(output_type, output);
+                print_output(output_type, &output);
+            }
+            if should_notify {
+                release_plz_core::send_notifications(
+                    &config.notifications(),
+                    NotificationEvent::Release,
+                    &output,
+                )
+                .await?;
             }
         }
         Command::GenerateCompletions(cmd_args) => cmd_args.print(),
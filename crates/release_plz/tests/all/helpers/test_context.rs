@@ -10,7 +10,7 @@ use cargo_metadata::{
 use cargo_utils::{CARGO_TOML, LocalManifest};
 use git_cmd::Repo;
 use release_plz_core::{
-    DEFAULT_BRANCH_PREFIX, GitClient, GitForge, GitPr, Gitea, Pr, RepoUrl,
+    DEFAULT_BRANCH_PREFIX, GitClient, GitForge, GitPr, Gitea, Pr, PrReviewers, RepoUrl,
     fs_utils::{Utf8TempDir, canonicalize_utf8},
 };
 use secrecy::SecretString;
@@ -84,6 +84,7 @@ impl TestContext {
             body: "This is my pull request".to_string(),
             draft: false,
             labels: vec![],
+            reviewers: PrReviewers::default(),
         };
         self.git_client.open_pr(&pr).await.unwrap();
         // go back to main
@@ -205,6 +206,24 @@ impl TestContext {
             .assert()
     }
 
+    pub fn run_release_to_local_registry(&self, local_registry: &Utf8Path) -> Assert {
+        super::cmd::release_plz_cmd()
+            .current_dir(self.repo_dir())
+            .env(RELEASE_PLZ_LOG, log_level())
+            .arg("release")
+            .arg("--verbose")
+            .arg("--git-token")
+            .arg(&self.gitea.token)
+            .arg("--forge")
+            .arg("gitea")
+            .arg("--local-registry")
+            .arg(local_registry)
+            .arg("--output")
+            .arg("json")
+            .timeout(Duration::from_secs(300))
+            .assert()
+    }
+
     pub fn repo_dir(&self) -> Utf8PathBuf {
         let path = self.test_dir.path().join(&self.gitea.repo);
         canonicalize_utf8(&path).unwrap()
@@ -30,6 +30,8 @@ and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0
     let expected_stdout = serde_json::json!({
         "releases": [
             {
+                "assets": [],
+                "failed_hooks": [],
                 "package_name": crate_name,
                 "tag": "v0.1.0",
                 "version": "0.1.0",
@@ -72,6 +74,8 @@ async fn release_plz_releases_a_new_project_with_custom_tag_name() {
     let expected_stdout = serde_json::json!({
         "releases": [
             {
+                "assets": [],
+                "failed_hooks": [],
                 "package_name": crate_name,
                 "prs": [],
                 "tag": expected_tag,
@@ -188,6 +192,8 @@ async fn release_plz_does_not_releases_twice() {
     let expected_stdout = serde_json::json!({
         "releases": [
             {
+                "assets": [],
+                "failed_hooks": [],
                 "package_name": crate_name,
                 "prs": [],
                 "tag": "v0.1.0",
@@ -202,3 +208,37 @@ async fn release_plz_does_not_releases_twice() {
     let outcome = context.run_release().success();
     outcome.stdout("{\"releases\":[]}\n");
 }
+
+#[tokio::test]
+#[cfg_attr(not(feature = "docker-tests"), ignore)]
+async fn release_plz_publishes_to_local_registry() {
+    let context = TestContext::new().await;
+    let local_registry = Utf8TempDir::new().unwrap();
+    let crate_name = &context.gitea.repo;
+
+    let outcome = context
+        .run_release_to_local_registry(local_registry.path())
+        .success();
+    let expected_stdout = serde_json::json!({
+        "releases": [
+            {
+                "assets": [],
+                "failed_hooks": [],
+                "package_name": crate_name,
+                "prs": [],
+                "tag": "v0.1.0",
+                "version": "0.1.0",
+            }
+        ]
+    })
+    .to_string();
+    outcome.stdout(format!("{expected_stdout}\n"));
+
+    let crate_file = local_registry
+        .path()
+        .join("crates")
+        .join(crate_name)
+        .join(format!("{crate_name}-0.1.0.crate"));
+    assert!(crate_file.exists());
+    assert!(context.repo.tag_exists("v0.1.0").unwrap());
+}
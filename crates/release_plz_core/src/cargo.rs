@@ -3,12 +3,15 @@ use cargo_metadata::{Package, camino::Utf8Path};
 use crates_index::{Crate, GitIndex, SparseIndex};
 use tracing::{debug, info};
 
+use crate::LocalRegistry;
+
 use http::{Version, header};
 use secrecy::{ExposeSecret, SecretString};
 use std::{
     env,
     error::Error as _,
-    process::{Command, ExitStatus},
+    process::{Command, ExitStatus, Output},
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
@@ -21,8 +24,10 @@ pub struct CargoRegistry {
 
 #[allow(clippy::large_enum_variant)]
 pub enum CargoIndex {
-    Git(GitIndex),
+    /// Shared with the blocking task that updates the index.
+    Git(Arc<Mutex<GitIndex>>),
     Sparse(SparseIndex),
+    Local(LocalRegistry),
 }
 
 fn cargo_cmd() -> Command {
@@ -39,6 +44,37 @@ pub fn run_cargo(root: &Utf8Path, args: &[&str]) -> anyhow::Result<CmdOutput> {
         .output()
         .context("cannot run cargo")?;
 
+    cmd_output(output)
+}
+
+/// Same as [`run_cargo`], but it doesn't block the async runtime,
+/// so that multiple cargo commands can run concurrently.
+pub async fn run_cargo_async(root: &Utf8Path, args: &[&str]) -> anyhow::Result<CmdOutput> {
+    run_cargo_with_env_async(root, args, &[]).await
+}
+
+/// Same as [`run_cargo_async`], with additional environment variables.
+/// Use environment variables to pass secrets, because command line arguments
+/// are visible in the process list.
+pub async fn run_cargo_with_env_async(
+    root: &Utf8Path,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> anyhow::Result<CmdOutput> {
+    debug!("cargo {}", args.join(" "));
+
+    let output = tokio::process::Command::from(cargo_cmd())
+        .current_dir(root)
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .await
+        .context("cannot run cargo")?;
+
+    cmd_output(output)
+}
+
+fn cmd_output(output: Output) -> anyhow::Result<CmdOutput> {
     let output_stdout = String::from_utf8(output.stdout)?;
     let output_stderr = String::from_utf8(output.stderr)?;
 
@@ -73,30 +109,45 @@ pub async fn is_published(
 ) -> anyhow::Result<bool> {
     tokio::time::timeout(timeout, async {
         match index {
-            CargoIndex::Git(index) => is_published_git(index, package),
+            CargoIndex::Git(index) => is_published_git(index, package).await,
             CargoIndex::Sparse(index) => is_in_cache_sparse(index, package, token).await,
+            CargoIndex::Local(registry) => registry.is_published(package),
         }
     })
     .await?
     .with_context(|| format!("timeout while publishing {}", package.name))
 }
 
-pub fn is_published_git(index: &mut GitIndex, package: &Package) -> anyhow::Result<bool> {
-    // See if we already have the package in cache.
-    if is_in_cache_git(index, package) {
-        return Ok(true);
-    }
+/// Updating the git index is blocking, so it runs in a blocking task to avoid
+/// blocking the packages released concurrently.
+pub async fn is_published_git(
+    index: &Arc<Mutex<GitIndex>>,
+    package: &Package,
+) -> anyhow::Result<bool> {
+    let index = Arc::clone(index);
+    let name = package.name.to_string();
+    let version = package.version.to_string();
+    tokio::task::spawn_blocking(move || {
+        let mut index = index
+            .lock()
+            .map_err(|e| anyhow::anyhow!("can't lock the git index: {e}"))?;
+        // See if we already have the package in cache.
+        if is_in_cache_git(&index, &name, &version) {
+            return Ok(true);
+        }
 
-    // The package is not in the cache, so we update the cache.
-    index.update().context("failed to update git index")?;
+        // The package is not in the cache, so we update the cache.
+        index.update().context("failed to update git index")?;
 
-    // Try again with updated index.
-    Ok(is_in_cache_git(index, package))
+        // Try again with updated index.
+        Ok(is_in_cache_git(&index, &name, &version))
+    })
+    .await
+    .context("git index task panicked")?
 }
 
-fn is_in_cache_git(index: &GitIndex, package: &Package) -> bool {
-    let crate_data = index.crate_(&package.name);
-    let version = &package.version.to_string();
+fn is_in_cache_git(index: &GitIndex, name: &str, version: &str) -> bool {
+    let crate_data = index.crate_(name);
     is_in_cache(crate_data.as_ref(), version)
 }
 
@@ -0,0 +1,141 @@
+//! Owners of the paths of the repository, read from the `CODEOWNERS` file
+//! supported by GitHub, GitLab and Gitea.
+
+use anyhow::Context as _;
+use cargo_metadata::camino::Utf8Path;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tracing::debug;
+
+use crate::PrReviewers;
+
+/// Locations of the `CODEOWNERS` file, relative to the repository root.
+/// The first file that exists is used.
+const CODEOWNERS_PATHS: &[&str] = &[
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    ".gitea/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// Rules of a `CODEOWNERS` file.
+#[derive(Debug)]
+pub struct CodeOwners {
+    /// Pattern and owners of each rule, in the order of the file.
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Read the `CODEOWNERS` file of the repository.
+    /// Return [`Option::None`] if the repository doesn't have one.
+    pub fn from_repo(repo_root: &Utf8Path) -> anyhow::Result<Option<Self>> {
+        let Some(path) = CODEOWNERS_PATHS
+            .iter()
+            .map(|path| repo_root.join(path))
+            .find(|path| path.is_file())
+        else {
+            debug!("the repository doesn't have a CODEOWNERS file");
+            return Ok(None);
+        };
+        let content = fs_err::read_to_string(&path)?;
+        let code_owners =
+            Self::parse(repo_root, &content).with_context(|| format!("can't parse {path}"))?;
+        Ok(Some(code_owners))
+    }
+
+    fn parse(repo_root: &Utf8Path, content: &str) -> anyhow::Result<Self> {
+        let mut rules = vec![];
+        for line in content.lines() {
+            let line = line.trim();
+            // Skip comments and the sections of GitLab, e.g. `[Documentation]` or `^[Docs][2] @owner`.
+            if line.is_empty() || line.starts_with('#') || line.starts_with(['[', '^']) {
+                continue;
+            }
+            let mut tokens = line.split_whitespace().take_while(|t| !t.starts_with('#'));
+            let Some(pattern) = tokens.next() else {
+                continue;
+            };
+            let owners: Vec<String> = tokens.map(str::to_string).collect();
+            let mut builder = GitignoreBuilder::new(repo_root);
+            builder
+                .add_line(None, pattern)
+                .with_context(|| format!("invalid pattern `{pattern}`"))?;
+            let matcher = builder
+                .build()
+                .with_context(|| format!("invalid pattern `{pattern}`"))?;
+            rules.push((matcher, owners));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Owners of the directory, relative to the repository root.
+    /// Like in `CODEOWNERS` files, the last matching rule wins.
+    pub fn owners(&self, dir: &Utf8Path) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(matcher.path().join(dir), true)
+                    .is_ignore()
+            })
+            .map_or(&[], |(_, owners)| owners.as_slice())
+    }
+
+    /// Reviewers of the directory, relative to the repository root.
+    /// Owners like `@org/team` are team reviewers and owners like `@user` are reviewers.
+    /// Email addresses are ignored, because forges request reviews by user name.
+    pub fn reviewers(&self, dir: &Utf8Path) -> PrReviewers {
+        let mut reviewers = PrReviewers::default();
+        for owner in self.owners(dir) {
+            let Some(owner) = owner.strip_prefix('@') else {
+                debug!("ignoring code owner {owner}: it isn't a user or a team");
+                continue;
+            };
+            match owner.split_once('/') {
+                Some((_org, team)) => reviewers.team_reviewers.push(team.to_string()),
+                None => reviewers.reviewers.push(owner.to_string()),
+            }
+        }
+        reviewers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let code_owners = CodeOwners::parse(
+            Utf8Path::new("/repo"),
+            "# Default owners
+* @alice
+
+/crates/ @bob @my-org/core-team # crates
+/crates/cli/ @carol
+[Docs]
+docs/ @dave user@example.com
+",
+        )
+        .unwrap();
+        assert_eq!(code_owners.owners(Utf8Path::new("other")), ["@alice"]);
+        assert_eq!(
+            code_owners.owners(Utf8Path::new("crates/core")),
+            ["@bob", "@my-org/core-team"]
+        );
+        assert_eq!(code_owners.owners(Utf8Path::new("crates/cli")), ["@carol"]);
+        assert_eq!(
+            code_owners.reviewers(Utf8Path::new("crates/core")),
+            PrReviewers {
+                reviewers: vec!["bob".to_string()],
+                team_reviewers: vec!["core-team".to_string()],
+                assignees: vec![],
+            }
+        );
+        assert_eq!(
+            code_owners.reviewers(Utf8Path::new("docs")).reviewers,
+            ["dave"]
+        );
+    }
+}
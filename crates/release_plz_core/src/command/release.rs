@@ -1,6 +1,7 @@
 use std::{
     collections::{BTreeMap, HashSet},
-    time::Duration,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
@@ -10,7 +11,9 @@ use cargo_metadata::{
     camino::{Utf8Path, Utf8PathBuf},
     semver::Version,
 };
+use chrono::Utc;
 use crates_index::{GitIndex, SparseIndex};
+use futures::StreamExt as _;
 use git_cmd::Repo;
 use secrecy::{ExposeSecret, SecretString};
 use serde::Serialize;
@@ -18,15 +21,41 @@ use tracing::{debug, info, instrument, trace, warn};
 use url::Url;
 
 use crate::{
-    CHANGELOG_FILENAME, DEFAULT_BRANCH_PREFIX, GitForge, PackagePath, Project, Publishable as _,
-    ReleaseMetadata, ReleaseMetadataBuilder, Remote,
-    cargo::{CargoIndex, CargoRegistry, CmdOutput, is_published, run_cargo, wait_until_published},
+    CHANGELOG_FILENAME, CRATES_IO_REGISTRY_NAME, DEFAULT_BRANCH_PREFIX, FreezeWindow, GitForge,
+    LocalRegistry, PackageContentsLimits, PackagePath, Project, PublishError, PublishErrorKind,
+    Publishable as _, RegistryConfig, ReleaseGates, ReleaseMetadata, ReleaseMetadataBuilder,
+    ReleasedPrsConfig, Remote, add_secret_string,
+    cargo::{
+        CargoIndex, CargoRegistry, CmdOutput, is_published, run_cargo_async,
+        run_cargo_with_env_async, wait_until_published,
+    },
     cargo_hash_kind::get_hash_kind,
     changelog_parser,
-    git::forge::GitClient,
+    git::forge::{GitClient, ReleaseAsset},
+    hooks::{FailedHook, HookContext, HookKind, Hooks, run_optional_hook, run_required_hook},
+    metadata_lint::{is_published_to_crates_io, lint_metadata},
+    milestone::{close_milestone, milestone_title},
+    package_contents::PackageContents,
     pr_parser::{Pr, prs_from_text},
+    publish_verification::verify_published_package,
+    release_freeze::active_freeze,
+    release_journal::{PackageJournal, ReleaseJournal},
+    release_order::{is_dependency_of_any, release_levels},
+    tera::{CHANGELOG_VAR, PACKAGE_VAR, VERSION_VAR, render_template, tera_context, tera_var},
 };
 
+const RELEASE_JOURNAL_FILENAME: &str = "release-journal.json";
+/// Registry name used for the local registry in the journal and in the hooks.
+const LOCAL_REGISTRY_NAME: &str = "local";
+
+/// Cargo commands that run in the working tree of the workspace.
+/// `cargo publish` of different packages can run concurrently, while the verification
+/// of the published packages needs exclusive access, because it edits the `Cargo.lock` file.
+static WORKING_TREE: tokio::sync::RwLock<()> = tokio::sync::RwLock::const_new(());
+/// Time to wait before publishing again when the registry rate limits the upload
+/// without telling how long to wait.
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 pub struct ReleaseRequest {
     /// Cargo metadata.
@@ -38,6 +67,12 @@ pub struct ReleaseRequest {
     registry: Option<String>,
     /// Token used to publish to the cargo registry.
     token: Option<SecretString>,
+    /// Registry-specific configurations.
+    /// The key is the registry name in the Cargo config. crates.io is `crates-io`.
+    registries: BTreeMap<String, RegistryConfig>,
+    /// Directory where the packages are published instead of a cargo registry.
+    /// If set, `registry` and `token` are ignored.
+    local_registry: Option<LocalRegistry>,
     /// Perform all checks without uploading.
     dry_run: bool,
     /// If true, release on every commit.
@@ -53,8 +88,30 @@ pub struct ReleaseRequest {
     packages_config: PackagesConfig,
     /// publish timeout
     publish_timeout: Duration,
+    /// Maximum number of packages published at the same time.
+    publish_concurrency: usize,
+    /// If true, package and verify all the packages before publishing any of them.
+    publish_preflight: bool,
+    /// If true, check the crates.io metadata of the packages before publishing any of them.
+    metadata_lint: bool,
     /// PR Branch Prefix
     branch_prefix: String,
+    /// Conditions that the merged release PR must meet to publish the packages.
+    gates: ReleaseGates,
+    /// Periods of time when the packages must not be published.
+    freeze_windows: Vec<FreezeWindow>,
+    /// How to update the PRs and issues included in the release.
+    released_prs: ReleasedPrsConfig,
+    /// Tera template of the title of the milestone to close after releasing a package.
+    milestone_to_close: Option<String>,
+    /// If true, publish the packages even during a release freeze.
+    ignore_freeze: bool,
+    /// If true, use the release journal to complete the steps
+    /// that a previous interrupted release didn't perform.
+    resume: bool,
+    /// File where release-plz saves the progress of the release.
+    /// If unspecified, it's saved in the target directory.
+    journal_path: Option<Utf8PathBuf>,
 }
 
 impl ReleaseRequest {
@@ -64,13 +121,25 @@ impl ReleaseRequest {
             metadata,
             registry: None,
             token: None,
+            registries: BTreeMap::new(),
+            local_registry: None,
             dry_run: false,
             git_release: None,
             repo_url: None,
             packages_config: PackagesConfig::default(),
             publish_timeout: minutes_30,
+            publish_concurrency: 1,
+            publish_preflight: false,
+            metadata_lint: true,
             release_always: true,
             branch_prefix: DEFAULT_BRANCH_PREFIX.to_string(),
+            gates: ReleaseGates::default(),
+            freeze_windows: vec![],
+            ignore_freeze: false,
+            released_prs: ReleasedPrsConfig::default(),
+            milestone_to_close: None,
+            resume: false,
+            journal_path: None,
         }
     }
 
@@ -89,6 +158,13 @@ impl ReleaseRequest {
         self
     }
 
+    /// Publish the packages to a directory instead of a cargo registry,
+    /// e.g. to rehearse a release without network.
+    pub fn with_local_registry(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.local_registry = Some(LocalRegistry::new(path));
+        self
+    }
+
     pub fn with_dry_run(mut self, dry_run: bool) -> Self {
         self.dry_run = dry_run;
         self
@@ -109,16 +185,68 @@ impl ReleaseRequest {
         self
     }
 
+    /// Set the token and publish settings of a registry.
+    pub fn with_registry_config(
+        mut self,
+        registry: impl Into<String>,
+        config: RegistryConfig,
+    ) -> Self {
+        self.registries.insert(registry.into(), config);
+        self
+    }
+
     pub fn with_publish_timeout(mut self, timeout: Duration) -> Self {
         self.publish_timeout = timeout;
         self
     }
 
+    /// Publish up to `concurrency` packages at the same time.
+    /// Only packages that don't depend on each other are published concurrently.
+    pub fn with_publish_concurrency(mut self, concurrency: usize) -> Self {
+        self.publish_concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn with_publish_preflight(mut self, publish_preflight: bool) -> Self {
+        self.publish_preflight = publish_preflight;
+        self
+    }
+
+    pub fn with_metadata_lint(mut self, metadata_lint: bool) -> Self {
+        self.metadata_lint = metadata_lint;
+        self
+    }
+
     pub fn with_release_always(mut self, release_always: bool) -> Self {
         self.release_always = release_always;
         self
     }
 
+    pub fn with_gates(mut self, gates: ReleaseGates) -> Self {
+        self.gates = gates;
+        self
+    }
+
+    pub fn with_freeze_windows(mut self, freeze_windows: Vec<FreezeWindow>) -> Self {
+        self.freeze_windows = freeze_windows;
+        self
+    }
+
+    pub fn with_ignore_freeze(mut self, ignore_freeze: bool) -> Self {
+        self.ignore_freeze = ignore_freeze;
+        self
+    }
+
+    pub fn with_released_prs_config(mut self, released_prs: ReleasedPrsConfig) -> Self {
+        self.released_prs = released_prs;
+        self
+    }
+
+    pub fn with_milestone_to_close(mut self, milestone_template: impl Into<String>) -> Self {
+        self.milestone_to_close = Some(milestone_template.into());
+        self
+    }
+
     pub fn with_branch_prefix(mut self, pr_branch_prefix: Option<String>) -> Self {
         if let Some(branch_prefix) = pr_branch_prefix {
             self.branch_prefix = branch_prefix;
@@ -126,6 +254,26 @@ impl ReleaseRequest {
         self
     }
 
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn with_journal_path(mut self, journal_path: impl Into<Utf8PathBuf>) -> Self {
+        self.journal_path = Some(journal_path.into());
+        self
+    }
+
+    /// File where the release journal is saved.
+    pub fn journal_path(&self) -> Utf8PathBuf {
+        self.journal_path.clone().unwrap_or_else(|| {
+            self.metadata
+                .target_directory
+                .join("release-plz")
+                .join(RELEASE_JOURNAL_FILENAME)
+        })
+    }
+
     /// Set release config for a specific package.
     pub fn with_package_config(
         mut self,
@@ -154,6 +302,16 @@ impl ReleaseRequest {
         config.publish.enabled
     }
 
+    fn should_verify_upload(&self, package: &str) -> bool {
+        let config = self.get_package_config(package);
+        config.publish.verify_upload
+    }
+
+    fn package_contents_limits(&self, package: &str) -> PackageContentsLimits {
+        let config = self.get_package_config(package);
+        config.publish.contents_limits
+    }
+
     fn is_git_release_enabled(&self, package: &str) -> bool {
         let config = self.get_package_config(package);
         config.git_release.enabled
@@ -188,15 +346,64 @@ impl ReleaseRequest {
         config.all_features
     }
 
-    /// Find the token to use for the given `registry` ([`Option::None`] means crates.io).
-    fn find_registry_token(&self, registry: Option<&str>) -> anyhow::Result<Option<SecretString>> {
+    fn registry_config(&self, registry: Option<&str>) -> Option<&RegistryConfig> {
+        self.registries
+            .get(registry.unwrap_or(CRATES_IO_REGISTRY_NAME))
+    }
+
+    /// Timeout to wait for the package to be published in the given `registry`.
+    fn publish_timeout(&self, registry: Option<&str>) -> Duration {
+        self.registry_config(registry)
+            .and_then(RegistryConfig::publish_timeout)
+            .unwrap_or(self.publish_timeout)
+    }
+
+    /// Whether to pass `--no-verify` when publishing the package to the given `registry`.
+    fn no_verify_for_registry(&self, package: &str, registry: Option<&str>) -> bool {
+        self.registry_config(registry)
+            .and_then(RegistryConfig::no_verify)
+            .unwrap_or_else(|| self.no_verify(package))
+    }
+
+    /// Token explicitly configured for the given `registry`
+    /// ([`Option::None`] means crates.io), either in the request or in the registry config.
+    fn configured_registry_token(
+        &self,
+        registry: Option<&str>,
+    ) -> anyhow::Result<Option<SecretString>> {
         let is_registry_same_as_request = self.registry.as_deref() == registry;
-        let token = is_registry_same_as_request
+        if let Some(token) = is_registry_same_as_request
             .then(|| self.token.clone())
             .flatten()
-            // if the registry is not the same as the request or if there's no token in the request,
-            // try to find the token in the Cargo credentials file or in the environment variables.
-            .or(cargo_utils::registry_token(self.registry.as_deref())?);
+        {
+            return Ok(Some(token));
+        }
+        let token = match self.registry_config(registry) {
+            Some(config) => config.token().with_context(|| {
+                format!(
+                    "can't read the token of registry `{}`",
+                    registry.unwrap_or(CRATES_IO_REGISTRY_NAME)
+                )
+            })?,
+            None => None,
+        };
+        if let Some(token) = &token {
+            add_secret_string(token);
+        }
+        Ok(token)
+    }
+
+    /// Find the token to use for the given `registry` ([`Option::None`] means crates.io).
+    fn find_registry_token(&self, registry: Option<&str>) -> anyhow::Result<Option<SecretString>> {
+        let token = match self.configured_registry_token(registry)? {
+            Some(token) => Some(token),
+            // If there's no configured token, try to find the token
+            // in the Cargo credentials file or in the environment variables.
+            None => cargo_utils::registry_token(self.local_manifest().as_std_path(), registry)?,
+        };
+        if let Some(token) = &token {
+            add_secret_string(token);
+        }
         Ok(token)
     }
 
@@ -302,6 +509,8 @@ pub struct ReleaseConfig {
     /// Whether this package has a changelog that release-plz updates or not.
     /// Default: `true`.
     changelog_update: bool,
+    /// Commands to run while releasing the package.
+    hooks: Hooks,
 }
 
 impl ReleaseConfig {
@@ -355,6 +564,11 @@ impl ReleaseConfig {
         self
     }
 
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     pub fn publish(&self) -> &PublishConfig {
         &self.publish
     }
@@ -377,6 +591,7 @@ impl Default for ReleaseConfig {
             release: true,
             changelog_path: None,
             changelog_update: true,
+            hooks: Hooks::default(),
         }
     }
 }
@@ -384,6 +599,11 @@ impl Default for ReleaseConfig {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PublishConfig {
     enabled: bool,
+    /// After publishing, download the package from the registry and
+    /// check that it matches the released source.
+    verify_upload: bool,
+    /// Limits that the files of the package must respect to be published.
+    contents_limits: PackageContentsLimits,
 }
 
 impl Default for PublishConfig {
@@ -394,7 +614,21 @@ impl Default for PublishConfig {
 
 impl PublishConfig {
     pub fn enabled(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            verify_upload: false,
+            contents_limits: PackageContentsLimits::default(),
+        }
+    }
+
+    pub fn with_contents_limits(mut self, contents_limits: PackageContentsLimits) -> Self {
+        self.contents_limits = contents_limits;
+        self
+    }
+
+    pub fn with_verify_upload(mut self, verify_upload: bool) -> Self {
+        self.verify_upload = verify_upload;
+        self
     }
 
     pub fn is_enabled(&self) -> bool {
@@ -410,6 +644,19 @@ pub enum ReleaseType {
     Auto,
 }
 
+/// What to do when the git release of the tag already exists,
+/// e.g. because it was created manually or by a previous run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExistingReleasePolicy {
+    /// Leave the existing release as it is.
+    Skip,
+    /// Update the existing release with the current name, body and flags.
+    Update,
+    /// Return an error.
+    #[default]
+    Fail,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitReleaseConfig {
     enabled: bool,
@@ -418,6 +665,10 @@ pub struct GitReleaseConfig {
     release_type: ReleaseType,
     name_template: Option<String>,
     body_template: Option<String>,
+    /// Glob patterns of the files to upload to the git release.
+    /// They are tera templates.
+    assets: Vec<String>,
+    if_exists: ExistingReleasePolicy,
 }
 
 impl Default for GitReleaseConfig {
@@ -435,6 +686,8 @@ impl GitReleaseConfig {
             release_type: ReleaseType::default(),
             name_template: None,
             body_template: None,
+            assets: vec![],
+            if_exists: ExistingReleasePolicy::default(),
         }
     }
 
@@ -467,6 +720,16 @@ impl GitReleaseConfig {
         self
     }
 
+    pub fn set_assets(mut self, assets: Vec<String>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    pub fn set_if_exists(mut self, if_exists: ExistingReleasePolicy) -> Self {
+        self.if_exists = if_exists;
+        self
+    }
+
     pub fn is_pre_release(&self, version: &Version) -> bool {
         match self.release_type {
             ReleaseType::Pre => true,
@@ -480,6 +743,10 @@ impl GitReleaseConfig {
 pub struct GitTagConfig {
     enabled: bool,
     name_template: Option<String>,
+    /// Tera template of the tag message.
+    message_template: Option<String>,
+    /// Sign the tag with the key configured in git.
+    sign: bool,
 }
 
 impl Default for GitTagConfig {
@@ -493,6 +760,8 @@ impl GitTagConfig {
         Self {
             enabled,
             name_template: None,
+            message_template: None,
+            sign: false,
         }
     }
 
@@ -501,6 +770,16 @@ impl GitTagConfig {
         self
     }
 
+    pub fn set_message_template(mut self, message_template: Option<String>) -> Self {
+        self.message_template = message_template;
+        self
+    }
+
+    pub fn set_sign(mut self, sign: bool) -> Self {
+        self.sign = sign;
+        self
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
@@ -515,10 +794,30 @@ pub struct GitRelease {
 #[derive(Serialize, Default, Debug)]
 pub struct Release {
     releases: Vec<PackageRelease>,
+    /// Why release-plz didn't publish the packages, if a release gate isn't met.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped_reason: Option<String>,
+}
+
+impl Release {
+    fn skipped(reason: String) -> Self {
+        Self {
+            releases: vec![],
+            skipped_reason: Some(reason),
+        }
+    }
+
+    pub fn skipped_reason(&self) -> Option<&str> {
+        self.skipped_reason.as_deref()
+    }
 }
 
 #[derive(Serialize, Debug)]
 pub struct PackageRelease {
+    /// Files uploaded to the git release.
+    assets: Vec<ReleaseAsset>,
+    /// Post hooks that failed. They don't stop the release.
+    failed_hooks: Vec<FailedHook>,
     package_name: String,
     prs: Vec<Pr>,
     /// Git tag name. It's not guaranteed that release-plz created the git tag.
@@ -549,6 +848,10 @@ pub async fn release(input: &ReleaseRequest) -> anyhow::Result<Option<Release>>
         debug!("skipping release");
         return Ok(None);
     }
+    if let ShouldRelease::Blocked(reason) = should_release {
+        info!("skipping release: {reason}");
+        return Ok(Some(Release::skipped(reason)));
+    }
 
     let mut checkout_done = false;
     if let ShouldRelease::YesWithCommit(commit) = &should_release {
@@ -588,22 +891,218 @@ async fn release_packages(
     if packages.is_empty() {
         info!("nothing to release");
     }
+    check_release_freeze(input, project, repo, &packages)?;
+    if input.metadata_lint {
+        lint_unreleased_packages(input, project, repo, &packages)
+            .context("metadata lint failed. No package was published")?;
+    }
+    check_package_contents(input, project, repo, &packages)
+        .context("package contents check failed. No package was published")?;
+    if input.publish_preflight {
+        preflight_check(input, project, repo, &packages)
+            .await
+            .context("pre-flight verification failed. No package was published")?;
+    }
 
     let mut package_releases: Vec<PackageRelease> = vec![];
     let hash_kind = get_hash_kind()?;
-    for package in packages {
-        if let Some(pkg_release) =
-            release_package_if_needed(input, project, package, repo, git_client, &hash_kind).await?
-        {
-            package_releases.push(pkg_release);
+    let journal = if input.dry_run {
+        // Don't save the progress of a dry run.
+        ReleaseJournal::default()
+    } else {
+        ReleaseJournal::load(&input.journal_path())?
+    };
+    // Packages of the same level don't depend on each other, so we can publish them concurrently.
+    for level in release_levels(&packages)? {
+        let level_releases: Vec<anyhow::Result<Option<PackageRelease>>> = futures::stream::iter(level.iter().map(|package| {
+                release_package_if_needed(
+                    input,
+                    project,
+                    package,
+                    repo,
+                    git_client,
+                    &hash_kind,
+                    &journal,
+                )
+            }))
+            // Wait for all the packages of the level to be released, even if one of them fails,
+            // so that we don't stop a publish halfway through.
+            .buffered(input.publish_concurrency)
+            .collect()
+            .await;
+        let mut failures = vec![];
+        for (package, pkg_release) in level.iter().copied().zip(level_releases) {
+            match pkg_release {
+                Ok(Some(pkg_release)) => package_releases.push(pkg_release),
+                Ok(None) => {}
+                Err(e) => failures.push((package, e)),
+            }
         }
+        level_failure(failures)?;
     }
     let release = (!package_releases.is_empty()).then_some(Release {
         releases: package_releases,
+        skipped_reason: None,
     });
     Ok(release)
 }
 
+/// Verify that all the packages to publish can be packaged before uploading any of them,
+/// so that an error in one of the last packages doesn't leave the workspace half-released.
+/// The error contains the failures of all the packages.
+async fn preflight_check(
+    input: &ReleaseRequest,
+    project: &Project,
+    repo: &Repo,
+    packages: &[&Package],
+) -> anyhow::Result<()> {
+    let mut failures = vec![];
+    if let Err(e) = project.check_mandatory_fields() {
+        failures.push(format!("{e:?}"));
+    }
+    // Packages to verify, by the registry where they are published.
+    let mut packages_by_registry: BTreeMap<Option<String>, Vec<&Package>> = BTreeMap::new();
+    for package in packages {
+        if !input.is_publish_enabled(&package.name) {
+            continue;
+        }
+        let git_tag = project.git_tag(&package.name, &package.version.to_string())?;
+        if repo.tag_exists(&git_tag)? {
+            // The package was already released.
+            continue;
+        }
+        for registry in publish_registries(package, input.registry.clone()) {
+            packages_by_registry
+                .entry(registry)
+                .or_default()
+                .push(package);
+        }
+    }
+    for (registry, packages) in &packages_by_registry {
+        let names = packages
+            .iter()
+            .map(|p| format!("{} {}", p.name, p.version))
+            .collect::<Vec<_>>()
+            .join(", ");
+        info!("verifying packages: {names}");
+        let output = run_cargo_package_all(
+            packages,
+            input,
+            &input.metadata.workspace_root,
+            registry.as_deref(),
+        )
+        .await
+        .context("failed to run cargo package")?;
+        if !output.status.success() || output.stderr.contains("error:") {
+            failures.push(format!("can't package {names}: {}", output.stderr));
+        }
+    }
+    if failures.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{} problem(s) found:\n\n{}",
+        failures.len(),
+        failures.join("\n\n")
+    )
+}
+
+/// Fail if there are packages to publish during a release freeze,
+/// unless the user asked to ignore it.
+fn check_release_freeze(
+    input: &ReleaseRequest,
+    project: &Project,
+    repo: &Repo,
+    packages: &[&Package],
+) -> anyhow::Result<()> {
+    let Some(freeze) = active_freeze(&input.freeze_windows, Utc::now()) else {
+        return Ok(());
+    };
+    if input.ignore_freeze {
+        warn!("{freeze}. Publishing anyway because the freeze is ignored");
+        return Ok(());
+    }
+    let mut packages_to_release = vec![];
+    for package in packages {
+        if !input.is_publish_enabled(&package.name) {
+            continue;
+        }
+        let git_tag = project.git_tag(&package.name, &package.version.to_string())?;
+        if !repo.tag_exists(&git_tag)? {
+            packages_to_release.push(format!("`{}`", package.name));
+        }
+    }
+    if packages_to_release.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{freeze}. No package was published. Packages waiting to be released: {}
+Note: to publish anyway, run `release-plz release --ignore-freeze`.",
+        packages_to_release.join(", ")
+    )
+}
+
+/// Lint the crates.io metadata of the packages that are going to be published to crates.io.
+fn lint_unreleased_packages(
+    input: &ReleaseRequest,
+    project: &Project,
+    repo: &Repo,
+    packages: &[&Package],
+) -> anyhow::Result<()> {
+    if input.registry.is_some() || input.local_registry.is_some() {
+        return Ok(());
+    }
+    let mut packages_to_lint = vec![];
+    for package in packages {
+        if !input.is_publish_enabled(&package.name) || !is_published_to_crates_io(package) {
+            continue;
+        }
+        let git_tag = project.git_tag(&package.name, &package.version.to_string())?;
+        if !repo.tag_exists(&git_tag)? {
+            packages_to_lint.push(*package);
+        }
+    }
+    lint_metadata(&packages_to_lint)
+}
+
+/// Check that the files of the packages to publish respect the configured limits,
+/// such as `max_package_size`.
+/// The error contains the problems of all the packages.
+fn check_package_contents(
+    input: &ReleaseRequest,
+    project: &Project,
+    repo: &Repo,
+    packages: &[&Package],
+) -> anyhow::Result<()> {
+    let mut problems = vec![];
+    for package in packages {
+        let limits = input.package_contents_limits(&package.name);
+        if limits.is_empty() || !input.is_publish_enabled(&package.name) {
+            continue;
+        }
+        let git_tag = project.git_tag(&package.name, &package.version.to_string())?;
+        if repo.tag_exists(&git_tag)? {
+            // The package was already released.
+            continue;
+        }
+        let contents = PackageContents::from_local_package(package.package_path()?)?;
+        problems.extend(
+            contents
+                .problems(&limits)?
+                .into_iter()
+                .map(|problem| format!("- `{}`: {problem}", package.name)),
+        );
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{} problem(s) found in the files of the packages:\n{}",
+        problems.len(),
+        problems.join("\n")
+    )
+}
+
 async fn release_package_if_needed(
     input: &ReleaseRequest,
     project: &Project,
@@ -611,20 +1110,53 @@ async fn release_package_if_needed(
     repo: &Repo,
     git_client: &GitClient,
     hash_kind: &crates_index::HashKind,
+    journal: &ReleaseJournal,
 ) -> anyhow::Result<Option<PackageRelease>> {
     let git_tag = project.git_tag(&package.name, &package.version.to_string())?;
     let release_name = project.release_name(&package.name, &package.version.to_string())?;
-    if repo.tag_exists(&git_tag)? {
-        info!(
-            "{} {}: Already published - Tag {} already exists",
-            package.name, package.version, &git_tag
-        );
-        return Ok(None);
+    // When resuming, the journal knows which steps of the interrupted release are done,
+    // so we can't rely on the existence of the tag or of the published package.
+    let interrupted_release = input
+        .resume
+        .then(|| journal.pending(&package.name, &package.version))
+        .flatten();
+    match &interrupted_release {
+        Some(_) => info!(
+            "{} {}: resuming interrupted release",
+            package.name, package.version
+        ),
+        None => {
+            let tag = git_tag.clone();
+            if run_git(repo, move |repo| repo.tag_exists(&tag)).await? {
+                info!(
+                    "{} {}: Already published - Tag {} already exists",
+                    package.name, package.version, &git_tag
+                );
+                return Ok(None);
+            }
+            if !input.dry_run {
+                if journal.pending(&package.name, &package.version).is_some() {
+                    warn!(
+                        "{} {}: discarding the progress of an interrupted release. Use `--resume` to complete interrupted releases",
+                        package.name, package.version
+                    );
+                }
+                journal.start(package)?;
+            }
+        }
     }
 
-    let registry_indexes = registry_indexes(package, input.registry.clone(), hash_kind)
-        .context("can't determine registry indexes")?;
+    let registry_indexes = match &input.local_registry {
+        Some(local_registry) => vec![CargoRegistry {
+            name: Some(LOCAL_REGISTRY_NAME.to_string()),
+            index: CargoIndex::Local(local_registry.clone()),
+        }],
+        None => registry_indexes(package, input.registry.clone(), hash_kind)
+            .context("can't determine registry indexes")?,
+    };
     let mut package_was_released = false;
+    let mut assets = vec![];
+    let mut failed_hooks = vec![];
     let changelog = last_changelog_entry(input, package);
     let prs = prs_from_text(&changelog);
     let release_info = ReleaseInfo {
@@ -633,26 +1165,79 @@ async fn release_package_if_needed(
         release_name: &release_name,
         changelog: &changelog,
         prs: &prs,
+        wait_for_index: is_dependency_of_any(package, &project.publishable_packages()),
     };
     for CargoRegistry { name, mut index } in registry_indexes {
         let token = input.find_registry_token(name.as_deref())?;
-        if is_published(&mut index, package, input.publish_timeout, &token)
+        let is_published_by_interrupted_release = interrupted_release
+            .as_ref()
+            .is_some_and(|p| p.is_published(name.as_deref()));
+        if !is_published_by_interrupted_release
+            && is_published(
+                &mut index,
+                package,
+                input.publish_timeout(name.as_deref()),
+                &token,
+            )
             .await
             .context("can't determine if package is published")?
         {
-            info!("{} {}: already published", package.name, package.version);
-            continue;
+            if interrupted_release.is_none() {
+                info!("{} {}: already published", package.name, package.version);
+                continue;
+            }
+            // The interrupted release uploaded the package, but it stopped before recording it.
+            // Complete the other steps of the release, e.g. the git tag and the git release.
+            info!(
+                "{} {}: already published by the interrupted release",
+                package.name, package.version
+            );
+            journal.record(package, |p| p.set_published(name.as_deref()))?;
         }
-        let package_was_released_at_index =
-            release_package(&mut index, input, repo, git_client, &release_info, &token)
+        let registry = RegistryInfo {
+            name: name.as_deref(),
+            index: &mut index,
+            token: &token,
+        };
+        let registry_release =
+            release_package(registry, input, repo, git_client, &release_info, journal)
                 .await
                 .context("failed to release package")?;
 
-        if package_was_released_at_index {
+        if let Some(registry_release) = registry_release {
             package_was_released = true;
+            assets.extend(registry_release.assets);
+            failed_hooks.extend(registry_release.failed_hooks);
+        }
+    }
+    if !input.dry_run {
+        journal.record(package, PackageJournal::set_completed)?;
+        if package_was_released {
+            // The package is already released, so a failure here shouldn't fail the release.
+            if let Err(e) = input.released_prs.update(git_client, package, &prs).await {
+                warn!(
+                    "{} {}: can't update the released PRs: {e:?}",
+                    package.name, package.version
+                );
+            }
+            if let Some(template) = &input.milestone_to_close {
+                let version = package.version.to_string();
+                let closed_milestone = async {
+                    let title = milestone_title(template, &package.name, &version)?;
+                    close_milestone(git_client, &title).await
+                };
+                if let Err(e) = closed_milestone.await {
+                    warn!(
+                        "{} {}: can't close the milestone: {e:?}",
+                        package.name, package.version
+                    );
+                }
+            }
         }
     }
     let package_release = package_was_released.then_some(PackageRelease {
+        assets,
+        failed_hooks,
         package_name: package.name.to_string(),
         version: package.version.clone(),
         tag: git_tag,
@@ -666,6 +1251,8 @@ enum ShouldRelease {
     Yes,
     YesWithCommit(String),
     No,
+    /// The release gates aren't met. The string contains the reason.
+    Blocked(String),
 }
 
 async fn should_release(
@@ -681,6 +1268,10 @@ async fn should_release(
 
     match associated_release_pr {
         Some(pr) => {
+            let unmet_gates = input.gates.unmet_gates(git_client, pr).await?;
+            if !unmet_gates.is_empty() {
+                return Ok(ShouldRelease::Blocked(unmet_gates.join("; ")));
+            }
             let pr_commits = git_client.pr_commits(pr.number).await?;
             // Get the last commit of the PR, i.e. the last commit that was pushed before the PR was merged
             match pr_commits.last() {
@@ -700,7 +1291,12 @@ async fn should_release(
             }
         }
         None => {
-            if input.release_always {
+            if input.release_always && !input.gates.is_empty() {
+                Ok(ShouldRelease::Blocked(
+                    "the current commit is not from a release PR, so the release gates can't be checked"
+                        .to_string(),
+                ))
+            } else if input.release_always {
                 Ok(ShouldRelease::Yes)
             } else {
                 info!("skipping release: current commit is not from a release PR");
@@ -727,11 +1323,9 @@ fn registry_indexes(
     registry: Option<String>,
     hash_kind: &crates_index::HashKind,
 ) -> anyhow::Result<Vec<CargoRegistry>> {
-    let registries = registry
-        .map(|r| vec![r])
-        .unwrap_or_else(|| package.publish.clone().unwrap_or_default());
-    let registry_urls = registries
+    let registry_urls = publish_registries(package, registry)
         .into_iter()
+        .flatten()
         .map(|r| {
             cargo_utils::registry_url(package.manifest_path.as_ref(), Some(&r))
                 .context("failed to retrieve registry url")
@@ -746,7 +1340,7 @@ fn registry_indexes(
                 SparseIndex::from_url_with_hash_kind(u.as_str(), hash_kind).map(CargoIndex::Sparse)
             } else {
                 GitIndex::from_url_with_hash_kind(&format!("registry+{u}"), hash_kind)
-                    .map(CargoIndex::Git)
+                    .map(|index| CargoIndex::Git(Arc::new(Mutex::new(index))))
             }
             .map(|index| CargoRegistry {
                 name: Some(registry),
@@ -757,62 +1351,108 @@ fn registry_indexes(
     if registry_indexes.is_empty() {
         registry_indexes.push(CargoRegistry {
             name: None,
-            index: CargoIndex::Git(GitIndex::new_cargo_default()?),
+            index: CargoIndex::Git(Arc::new(Mutex::new(GitIndex::new_cargo_default()?))),
         });
     }
     Ok(registry_indexes)
 }
 
+/// Names of the registries where the package is published.
+/// If `registry` is specified, it takes precedence over the `publish` field
+/// of the package manifest.
+/// [`Option::None`] means crates.io.
+fn publish_registries(package: &Package, registry: Option<String>) -> Vec<Option<String>> {
+    let registries = registry
+        .map(|r| vec![r])
+        .unwrap_or_else(|| package.publish.clone().unwrap_or_default());
+    if registries.is_empty() {
+        vec![None]
+    } else {
+        registries.into_iter().map(Some).collect()
+    }
+}
+
+struct RegistryInfo<'a> {
+    /// [`Option::None`] means crates.io.
+    name: Option<&'a str>,
+    index: &'a mut CargoIndex,
+    token: &'a Option<SecretString>,
+}
+
 struct ReleaseInfo<'a> {
     package: &'a Package,
     git_tag: &'a str,
     release_name: &'a str,
     changelog: &'a str,
     prs: &'a [Pr],
+    /// Other packages of the release depend on this package, so we need to wait
+    /// for it to be available in the registry index before publishing them.
+    wait_for_index: bool,
 }
 
-/// Return `true` if package was published, `false` otherwise.
+/// Outcome of the release of a package to a registry.
+struct RegistryRelease {
+    /// Files uploaded to the git release.
+    assets: Vec<ReleaseAsset>,
+    /// Post hooks that failed.
+    failed_hooks: Vec<FailedHook>,
+}
+
+/// Return [`Option::None`] if the package wasn't published.
 async fn release_package(
-    index: &mut CargoIndex,
+    registry: RegistryInfo<'_>,
     input: &ReleaseRequest,
     repo: &Repo,
     git_client: &GitClient,
     release_info: &ReleaseInfo<'_>,
-    token: &Option<SecretString>,
-) -> anyhow::Result<bool> {
+    journal: &ReleaseJournal,
+) -> anyhow::Result<Option<RegistryRelease>> {
     let workspace_root = &input.metadata.workspace_root;
+    let package = release_info.package;
+    let hooks = input.get_package_config(&package.name).hooks;
+    let version = package.version.to_string();
+    let hook_context = HookContext {
+        package: &package.name,
+        version: &version,
+        tag: release_info.git_tag,
+        registry: registry.name,
+        changelog: release_info.changelog,
+    };
+    let mut failed_hooks = vec![];
+    // Steps already performed by this run (for other registries) or by an interrupted run.
+    let progress = journal
+        .get(&package.name, &package.version)
+        .unwrap_or_else(|| PackageJournal::new(package.version.clone()));
 
-    let should_publish = input.is_publish_enabled(&release_info.package.name);
-    let should_create_git_tag = input.is_git_tag_enabled(&release_info.package.name);
-    let should_create_git_relase = input.is_git_release_enabled(&release_info.package.name);
+    let is_publish_enabled = input.is_publish_enabled(&package.name);
+    let should_publish = is_publish_enabled && !progress.is_published(registry.name);
+    let should_create_git_tag = input.is_git_tag_enabled(&package.name);
+    let should_create_git_relase = input.is_git_release_enabled(&package.name);
 
     if should_publish {
+        if !input.dry_run {
+            run_required_hook(&hooks, HookKind::PrePublish, &hook_context, workspace_root).await?;
+        }
         // Run `cargo publish`. Note that `--dry-run` is added if `input.dry_run` is true.
-        let output = run_cargo_publish(release_info.package, input, workspace_root)
-            .context("failed to run cargo publish")?;
-        if !output.status.success()
-            || !output.stderr.contains("Uploading")
-            || output.stderr.contains("error:")
-        {
-            if output.stderr.contains(&format!(
-                "crate version `{}` is already uploaded",
-                &release_info.package.version,
-            )) {
-                // The crate was published while `cargo publish` was running.
-                // Note that the crate wasn't published yet when `cargo publish` started,
-                // otherwise `cargo` would have returned the error "crate {package}@{version} already exists"
-                info!(
-                    "skipping publish of {} {}: already published",
-                    release_info.package.name, release_info.package.version
-                );
-                return Ok(false);
-            } else {
-                anyhow::bail!(
-                    "failed to publish {}: {}",
-                    release_info.package.name,
-                    output.stderr
-                );
+        if let Err(e) = publish_package(package, input, workspace_root, registry.name).await {
+            let is_already_uploaded = e.downcast_ref::<PublishError>().is_some_and(|e| {
+                e.kind() == PublishErrorKind::AlreadyUploaded
+                    && e.uploaded_version() == Some(version.as_str())
+            });
+            if !is_already_uploaded {
+                return Err(e.context(format!("failed to publish {}", package.name)));
             }
+            // The crate was published while `cargo publish` was running.
+            // Note that the crate wasn't published yet when `cargo publish` started,
+            // otherwise `cargo` would have returned the error "crate {package}@{version} already exists".
+            // The upload is done, so continue with the git tag and the git release.
+            info!(
+                "skipping publish of {} {}: already published",
+                package.name, package.version
+            );
+        }
+        if !input.dry_run {
+            journal.record(package, |p| p.set_published(registry.name))?;
         }
     }
 
@@ -823,38 +1463,97 @@ async fn release_package(
             should_create_git_tag,
             should_create_git_relase,
         );
-        Ok(false)
+        Ok(None)
     } else {
+        // The packages of the local registry aren't downloadable with cargo.
+        // When resuming, the upload is verified again if the interrupted release
+        // didn't verify it, e.g. because the verification failed.
+        let should_verify_upload = is_publish_enabled
+            && input.local_registry.is_none()
+            && input.should_verify_upload(&package.name)
+            && (should_publish || !progress.is_upload_verified(registry.name));
+        // The package must be in the index before we can download it.
+        // Packages that no other package of the release depends on are tagged right after
+        // the upload, so they don't block the release of their level.
+        if is_publish_enabled && (release_info.wait_for_index || should_verify_upload) {
+            wait_until_published(
+                registry.index,
+                package,
+                input.publish_timeout(registry.name),
+                registry.token,
+            )
+            .await?;
+        }
+        if should_verify_upload {
+            let tag = release_info.git_tag.to_string();
+            let commit = run_git(repo, move |repo| match repo.get_tag_commit(&tag) {
+                Some(commit) => Ok(commit),
+                None => repo.current_commit_hash(),
+            })
+            .await?;
+            // Don't let `cargo publish` use the `Cargo.lock` while the verification edits it.
+            let _working_tree = WORKING_TREE.write().await;
+            verify_published_package(package, registry.name, repo.directory(), &commit)
+                .await
+                .with_context(|| {
+                    format!(
+                        "verification of the published package {} {} failed",
+                        package.name, package.version
+                    )
+                })?;
+            journal.record(package, |p| p.set_upload_verified(registry.name))?;
+        }
         if should_publish {
-            wait_until_published(index, release_info.package, input.publish_timeout, token).await?;
+            run_optional_hook(
+                &hooks,
+                HookKind::PostPublish,
+                &hook_context,
+                workspace_root,
+                &mut failed_hooks,
+            )
+            .await;
         }
 
         if should_create_git_tag {
-            // Use same tag message of cargo-release
-            let message = format!(
-                "chore: Release package {} version {}",
-                release_info.package.name, release_info.package.version
-            );
-            repo.tag(release_info.git_tag, &message)?;
-            repo.push(release_info.git_tag)?;
+            if !progress.is_tagged() {
+                let tag_config = input.get_package_config(&package.name).git_tag;
+                let message = git_tag_message(&tag_config, package, release_info.changelog)?;
+                let tag = release_info.git_tag.to_string();
+                run_git(repo, move |repo| {
+                    create_git_tag(repo, &tag, &message, tag_config.sign)
+                })
+                .await?;
+                journal.record(package, PackageJournal::set_tagged)?;
+            }
+            if !progress.is_tag_pushed() {
+                let tag = release_info.git_tag.to_string();
+                run_git(repo, move |repo| repo.push(&tag)).await?;
+                journal.record(package, PackageJournal::set_tag_pushed)?;
+                run_optional_hook(
+                    &hooks,
+                    HookKind::PostTag,
+                    &hook_context,
+                    workspace_root,
+                    &mut failed_hooks,
+                )
+                .await;
+            }
         }
 
-        let contributors = get_contributors(release_info, git_client).await;
+        let mut assets = vec![];
+        if should_create_git_relase && !progress.are_git_release_assets_uploaded() {
+            let contributors = get_contributors(release_info, git_client).await;
 
-        // TODO fill the rest
-        let remote = Remote {
-            owner: "".to_string(),
-            repo: "".to_string(),
-            link: "".to_string(),
-            contributors,
-        };
-        if should_create_git_relase {
-            let release_body =
-                release_body(input, release_info.package, release_info.changelog, &remote);
-            let release_config = input
-                .get_package_config(&release_info.package.name)
-                .git_release;
-            let is_pre_release = release_config.is_pre_release(&release_info.package.version);
+            // TODO fill the rest
+            let remote = Remote {
+                owner: "".to_string(),
+                repo: "".to_string(),
+                link: "".to_string(),
+                contributors,
+            };
+            let release_body = release_body(input, package, release_info.changelog, &remote);
+            let release_config = input.get_package_config(&package.name).git_release;
+            let is_pre_release = release_config.is_pre_release(&package.version);
             let git_release_info = GitReleaseInfo {
                 git_tag: release_info.git_tag.to_string(),
                 release_name: release_info.release_name.to_string(),
@@ -862,16 +1561,138 @@ async fn release_package(
                 draft: release_config.draft,
                 latest: release_config.latest,
                 pre_release: is_pre_release,
+                assets: release_assets(workspace_root, package, &release_config)?,
+                if_exists: release_config.if_exists,
             };
-            git_client.create_release(&git_release_info).await?;
+            // When resuming, the release exists already, so we only upload the missing assets.
+            let should_upload_assets = progress.is_git_release_created()
+                || git_client.create_release(&git_release_info).await?;
+            journal.record(package, PackageJournal::set_git_release_created)?;
+            if should_upload_assets {
+                assets = git_client.upload_release_assets(&git_release_info).await?;
+            }
+            journal.record(package, PackageJournal::set_git_release_assets_uploaded)?;
+            run_optional_hook(
+                &hooks,
+                HookKind::PostRelease,
+                &hook_context,
+                workspace_root,
+                &mut failed_hooks,
+            )
+            .await;
         }
 
-        info!(
-            "published {} {}",
-            release_info.package.name, release_info.package.version
+        info!("published {} {}", package.name, package.version);
+        Ok(Some(RegistryRelease {
+            assets,
+            failed_hooks,
+        }))
+    }
+}
+
+/// Return an error if the release of any package of a level failed.
+/// If multiple packages failed, log every error, so that the user sees all of them.
+fn level_failure(mut failures: Vec<(&Package, anyhow::Error)>) -> anyhow::Result<()> {
+    if failures.len() <= 1 {
+        return failures.pop().map_or(Ok(()), |(_, e)| Err(e));
+    }
+    let mut failed_packages = vec![];
+    for (package, e) in failures {
+        tracing::error!(
+            "{} {}: release failed: {e:?}",
+            package.name,
+            package.version
         );
-        Ok(true)
+        failed_packages.push(package.name.to_string());
     }
+    anyhow::bail!(
+        "failed to release the packages {}. Fix the errors above and run the release again with `--resume`",
+        failed_packages.join(", ")
+    )
+}
+
+/// Run git in a blocking task, so that it doesn't block the releases of the other
+/// packages of the level.
+async fn run_git<T: Send + 'static>(
+    repo: &Repo,
+    f: impl FnOnce(&Repo) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    let repo = repo.clone();
+    tokio::task::spawn_blocking(move || f(&repo))
+        .await
+        .context("git task panicked")?
+}
+
+/// Create the git tag of the release.
+/// If signing was requested, fail if the created tag isn't signed.
+fn create_git_tag(repo: &Repo, tag: &str, message: &str, sign: bool) -> anyhow::Result<()> {
+    if !sign {
+        repo.tag(tag, message)?;
+        return Ok(());
+    }
+    repo.signed_tag(tag, message).with_context(|| {
+        format!("can't create signed tag {tag}. Check the signing configuration of git")
+    })?;
+    if !repo.is_tag_signed(tag)? {
+        // Don't leave an unsigned tag around: the next run would push it.
+        repo.delete_tag(tag)?;
+        anyhow::bail!(
+            "signing of tag {tag} was requested, but git created it without a signature. Check the signing configuration of git"
+        );
+    }
+    Ok(())
+}
+
+fn git_tag_message(
+    config: &GitTagConfig,
+    package: &Package,
+    changelog: &str,
+) -> anyhow::Result<String> {
+    // Use same tag message of cargo-release by default
+    let default_template = format!(
+        "chore: Release package {} version {}",
+        tera_var(PACKAGE_VAR),
+        tera_var(VERSION_VAR)
+    );
+    let template = config
+        .message_template
+        .as_deref()
+        .unwrap_or(&default_template);
+    let mut context = tera_context(&package.name, &package.version.to_string());
+    context.insert(CHANGELOG_VAR, changelog);
+    render_template(template, &context, "git_tag_message")
+}
+
+/// Files matching the asset patterns of the git release.
+/// Relative patterns are resolved from the workspace root.
+fn release_assets(
+    workspace_root: &Utf8Path,
+    package: &Package,
+    config: &GitReleaseConfig,
+) -> anyhow::Result<Vec<Utf8PathBuf>> {
+    let context = tera_context(&package.name, &package.version.to_string());
+    let mut assets = vec![];
+    for template in &config.assets {
+        let pattern = render_template(template, &context, "git_release_assets")?;
+        let pattern = workspace_root.join(pattern);
+        let paths = glob::glob(pattern.as_str())
+            .with_context(|| format!("invalid git release asset pattern `{pattern}`"))?;
+        let mut matched = false;
+        for path in paths {
+            let path = path.context("can't read git release asset")?;
+            let path = Utf8PathBuf::from_path_buf(path)
+                .map_err(|p| anyhow::anyhow!("non-utf8 git release asset path {p:?}"))?;
+            if path.is_file() {
+                assets.push(path);
+                matched = true;
+            }
+        }
+        anyhow::ensure!(
+            matched,
+            "git release asset pattern `{pattern}` doesn't match any file"
+        );
+    }
+    Ok(assets)
 }
 
 /// Traces the steps that would have been taken had release been run without dry-run.
@@ -954,6 +1775,10 @@ pub struct GitReleaseInfo {
     pub latest: Option<bool>,
     pub draft: bool,
     pub pre_release: bool,
+    /// Files to upload to the release.
+    pub assets: Vec<Utf8PathBuf>,
+    /// What to do if the release already exists.
+    pub if_exists: ExistingReleasePolicy,
 }
 
 /// Return `Err` if the `CARGO_REGISTRY_TOKEN` environment variable is set to an empty string in CI.
@@ -975,48 +1800,211 @@ fn verify_ci_cargo_registry_token() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn run_cargo_publish(
+/// Run `cargo publish`.
+/// If the registry rate limits the upload, wait the time it asks for and try again,
+/// until the `publish_timeout` of the registry elapses.
+/// If `cargo publish` fails, the error contains a [`PublishError`].
+/// [`Option::None`] registry means crates.io.
+async fn publish_package(
+    package: &Package,
+    input: &ReleaseRequest,
+    workspace_root: &Utf8Path,
+    registry: Option<&str>,
+) -> anyhow::Result<()> {
+    if let Some(local_registry) = &input.local_registry {
+        return publish_to_local_registry(package, input, workspace_root, local_registry).await;
+    }
+    let token = input.configured_registry_token(registry)?;
+    let publish_timeout = input.publish_timeout(registry);
+    let start = Instant::now();
+    loop {
+        let output = run_cargo_publish(package, input, workspace_root, registry, token.as_ref())
+            .await
+            .context("failed to run cargo publish")?;
+        if output.status.success()
+            && output.stderr.contains("Uploading")
+            && !output.stderr.contains("error:")
+        {
+            return Ok(());
+        }
+        let error = PublishError::from_stderr(&output.stderr);
+        if let PublishErrorKind::RateLimited { retry_after } = error.kind() {
+            let wait = retry_after.unwrap_or(DEFAULT_RATE_LIMIT_WAIT);
+            if start.elapsed() + wait < publish_timeout {
+                warn!(
+                    "{} {}: the registry rate limited the upload. Retrying in {wait:?}",
+                    package.name, package.version
+                );
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+            warn!(
+                "{} {}: the registry rate limited the upload. Not retrying, because waiting {wait:?} would exceed the `publish_timeout` of {:?}",
+                package.name, package.version, publish_timeout
+            );
+        }
+        return Err(error.into());
+    }
+}
+
+async fn run_cargo_publish(
     package: &Package,
     input: &ReleaseRequest,
     workspace_root: &Utf8Path,
+    registry: Option<&str>,
+    token: Option<&SecretString>,
 ) -> anyhow::Result<CmdOutput> {
+    let features = input.features(&package.name).join(",");
     let mut args = vec!["publish"];
-    args.push("--color");
-    args.push("always");
-    args.push("--manifest-path");
+    args.extend(packaging_args(package, input, registry, &features));
+    let token_env_var;
+    let mut envs = vec![];
+    if let Some(token) = token {
+        // Don't pass the token with `--token`, because command line arguments
+        // are visible in the process list.
+        token_env_var = cargo_utils::registry_token_env_var(registry)?;
+        envs.push((token_env_var.as_str(), token.expose_secret()));
+    } else {
+        verify_ci_cargo_registry_token()?;
+    }
+    if input.dry_run {
+        args.push("--dry-run");
+    }
+    let _working_tree = WORKING_TREE.read().await;
+    run_cargo_with_env_async(workspace_root, &args, &envs).await
+}
+
+/// Package the crate with `cargo package` and add it to the local registry.
+/// The workspace dependencies of the crate are packaged with it, so that cargo
+/// resolves their new versions without looking for them in crates.io.
+async fn publish_to_local_registry(
+    package: &Package,
+    input: &ReleaseRequest,
+    workspace_root: &Utf8Path,
+    local_registry: &LocalRegistry,
+) -> anyhow::Result<()> {
+    let mut packages = workspace_dependencies(package, &input.metadata);
+    packages.push(package);
+    let output = run_cargo_package_all(&packages, input, workspace_root, None)
+        .await
+        .context("failed to run cargo package")?;
+    if !output.status.success() {
+        return Err(PublishError::from_stderr(&output.stderr).into());
+    }
+    if input.dry_run {
+        return Ok(());
+    }
+    let crate_file = input
+        .metadata
+        .target_directory
+        .join("package")
+        .join(format!("{}-{}.crate", package.name, package.version));
+    local_registry.publish(package, &crate_file)
+}
+
+/// Workspace packages that the package depends on, directly or transitively.
+fn workspace_dependencies<'a>(package: &Package, metadata: &'a Metadata) -> Vec<&'a Package> {
+    let workspace_packages = metadata.workspace_packages();
+    let mut dependencies: Vec<&Package> = vec![];
+    let mut to_visit = vec![package];
+    while let Some(pkg) = to_visit.pop() {
+        for dep in pkg.dependencies.iter().filter(|dep| dep.path.is_some()) {
+            let Some(dep_package) = workspace_packages
+                .iter()
+                .find(|p| dep.name == *p.name && p.name != package.name)
+            else {
+                continue;
+            };
+            if !dependencies.iter().any(|d| d.name == dep_package.name) {
+                dependencies.push(dep_package);
+                to_visit.push(dep_package);
+            }
+        }
+    }
+    dependencies
+}
+
+/// Run `cargo package` for all the `packages` in one command, with the options used
+/// by `cargo publish` for the given `registry` ([`Option::None`] means crates.io).
+/// Cargo packages them together, so a package can depend on the new version
+/// of another package, even if it isn't published yet.
+/// The options that can't be set per package, like `--no-verify`, are enabled
+/// if at least one package enables them.
+async fn run_cargo_package_all(
+    packages: &[&Package],
+    input: &ReleaseRequest,
+    workspace_root: &Utf8Path,
+    registry: Option<&str>,
+) -> anyhow::Result<CmdOutput> {
+    let manifest_path = input.local_manifest();
+    let mut args = vec![
+        "package".to_string(),
+        "--color".to_string(),
+        "always".to_string(),
+        "--manifest-path".to_string(),
+        manifest_path.to_string(),
+    ];
+    for package in packages {
+        args.extend(["--package".to_string(), package.name.to_string()]);
+        args.extend(
+            input
+                .features(&package.name)
+                .iter()
+                .map(|feature| format!("--features={}/{feature}", package.name)),
+        );
+    }
+    if let Some(registry) = registry {
+        args.extend(["--registry".to_string(), registry.to_string()]);
+    }
+    if packages.iter().any(|p| input.allow_dirty(&p.name)) {
+        args.push("--allow-dirty".to_string());
+    }
+    if packages
+        .iter()
+        .any(|p| input.no_verify_for_registry(&p.name, registry))
+    {
+        args.push("--no-verify".to_string());
+    }
+    if packages.iter().any(|p| input.all_features(&p.name)) {
+        args.push("--all-features".to_string());
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run_cargo_async(workspace_root, &args).await
+}
+
+/// Arguments shared by `cargo package` and `cargo publish`.
+/// `registry` is the registry where the package is published ([`Option::None`] means crates.io).
+/// `features` is the comma-separated list of features to activate.
+fn packaging_args<'a>(
+    package: &'a Package,
+    input: &'a ReleaseRequest,
+    registry: Option<&'a str>,
+    features: &'a str,
+) -> Vec<&'a str> {
+    let mut args = vec!["--color", "always", "--manifest-path"];
     args.push(package.manifest_path.as_ref());
     // We specify the package name to allow publishing root packages.
     // See https://github.com/release-plz/release-plz/issues/1545
     args.push("--package");
     args.push(&package.name);
-    if let Some(registry) = &input.registry {
+    if let Some(registry) = registry {
         args.push("--registry");
         args.push(registry);
     }
-    if let Some(token) = &input.token {
-        args.push("--token");
-        args.push(token.expose_secret());
-    } else {
-        verify_ci_cargo_registry_token()?;
-    }
-    if input.dry_run {
-        args.push("--dry-run");
-    }
     if input.allow_dirty(&package.name) {
         args.push("--allow-dirty");
     }
-    if input.no_verify(&package.name) {
+    if input.no_verify_for_registry(&package.name, registry) {
         args.push("--no-verify");
     }
-    let features = input.features(&package.name).join(",");
     if !features.is_empty() {
         args.push("--features");
-        args.push(&features);
+        args.push(features);
     }
     if input.all_features(&package.name) {
         args.push("--all-features");
     }
-    run_cargo(workspace_root, &args)
+    args
 }
 
 /// Return an empty string if the changelog cannot be parsed.
@@ -1076,8 +2064,9 @@ fn last_changelog_entry(req: &ReleaseRequest, package: &Package) -> String {
 mod tests {
     use std::env;
     use std::ffi::OsStr;
-    use std::sync::{LazyLock, Mutex};
+    use std::sync::LazyLock;
 
+    use cargo_utils::CARGO_TOML;
     use fake_package::metadata::fake_metadata;
 
     use super::*;
@@ -1144,6 +2133,139 @@ mod tests {
         assert!(config.is_pre_release(&rc_version));
     }
 
+    /// Create a workspace where the package `release-plz-preflight-a` depends on
+    /// the version of `release-plz-preflight-b` that isn't published.
+    fn write_workspace_with_unpublished_dependency(root: &Utf8Path) {
+        fs_err::create_dir_all(root).unwrap();
+        fs_err::write(
+            root.join(CARGO_TOML),
+            "[workspace]\nmembers = [\"a\", \"b\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        // The new version of `b` isn't published, so `a` can only be packaged together with `b`.
+        for (name, dependencies) in [
+            (
+                "a",
+                r#"release-plz-preflight-b = { version = "7.7.7", path = "../b" }"#,
+            ),
+            ("b", ""),
+        ] {
+            fs_err::create_dir_all(root.join(name).join("src")).unwrap();
+            fs_err::write(root.join(name).join("src").join("lib.rs"), "").unwrap();
+            fs_err::write(
+                root.join(name).join(CARGO_TOML),
+                format!(
+                    "[package]\nname = \"release-plz-preflight-{name}\"\nversion = \"7.7.7\"\nedition = \"2021\"\ndescription = \"test\"\nlicense = \"MIT\"\n\n[dependencies]\n{dependencies}\n"
+                ),
+            )
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn packages_depending_on_unpublished_versions_are_packaged_together() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        write_workspace_with_unpublished_dependency(root);
+        let metadata = cargo_utils::get_manifest_metadata(&root.join(CARGO_TOML)).unwrap();
+        let packages: Vec<Package> = metadata.workspace_packages().into_iter().cloned().collect();
+        let packages: Vec<&Package> = packages.iter().collect();
+        let request = ReleaseRequest::new(metadata)
+            .with_default_package_config(ReleaseConfig::default().with_allow_dirty(true));
+
+        let output = run_cargo_package_all(&packages, &request, root, None)
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", output.stderr);
+    }
+
+    #[tokio::test]
+    async fn packages_depending_on_each_other_are_published_to_local_registry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        write_workspace_with_unpublished_dependency(&root.join("project"));
+        let metadata =
+            cargo_utils::get_manifest_metadata(&root.join("project").join(CARGO_TOML)).unwrap();
+        let packages: Vec<Package> = metadata.workspace_packages().into_iter().cloned().collect();
+        let local_registry = LocalRegistry::new(root.join("registry"));
+        let request = ReleaseRequest::new(metadata)
+            .with_default_package_config(ReleaseConfig::default().with_allow_dirty(true));
+        let workspace_root = request.metadata.workspace_root.clone();
+
+        // Publish `b` before `a`, like the release order does.
+        for package in packages.iter().rev() {
+            publish_to_local_registry(package, &request, &workspace_root, &local_registry)
+                .await
+                .unwrap();
+        }
+        for package in &packages {
+            assert!(local_registry.is_published(package).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_tags_package_uploaded_by_interrupted_release() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        let project_dir = root.join("project");
+        write_workspace_with_unpublished_dependency(&project_dir);
+        let repo = Repo::init(&project_dir);
+        let origin = root.join("origin.git");
+        git_cmd::git_in_dir(root, &["init", "--bare", origin.as_str()]).unwrap();
+        repo.git(&["remote", "add", "origin", origin.as_str()])
+            .unwrap();
+        // No PR is associated to the commit, because the mock server returns 404.
+        let forge = wiremock::MockServer::start().await;
+        let repo_url = crate::RepoUrl::new(&format!("{}/me/proj", forge.uri())).unwrap();
+        let gitea = crate::Gitea::new(repo_url, "token".into()).unwrap();
+
+        let metadata = cargo_utils::get_manifest_metadata(&project_dir.join(CARGO_TOML)).unwrap();
+        let request = ReleaseRequest::new(metadata)
+            .with_local_registry(root.join("registry"))
+            .with_journal_path(root.join("journal.json"))
+            .with_resume(true)
+            .with_release_always(true)
+            .with_git_release(GitRelease {
+                forge: GitForge::Gitea(gitea),
+            })
+            .with_default_package_config(
+                ReleaseConfig::default()
+                    .with_allow_dirty(true)
+                    .with_git_release(GitReleaseConfig::enabled(false)),
+            );
+        let package_b = request
+            .metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|p| *p.name == "release-plz-preflight-b")
+            .unwrap()
+            .clone();
+        // The interrupted release uploaded `b`, but it stopped before recording it in the journal.
+        publish_to_local_registry(
+            &package_b,
+            &request,
+            &request.metadata.workspace_root,
+            &LocalRegistry::new(root.join("registry")),
+        )
+        .await
+        .unwrap();
+        ReleaseJournal::load(&request.journal_path())
+            .unwrap()
+            .start(&package_b)
+            .unwrap();
+
+        release(&request).await.unwrap();
+
+        assert!(repo.tag_exists("release-plz-preflight-b-v7.7.7").unwrap());
+        assert!(repo.tag_exists("release-plz-preflight-a-v7.7.7").unwrap());
+        let journal = ReleaseJournal::load(&request.journal_path()).unwrap();
+        assert!(
+            journal
+                .pending("release-plz-preflight-b", &package_b.version)
+                .is_none()
+        );
+    }
+
     #[test]
     fn release_request_registry_token_env_works() {
         let registry_name = "my_registry";
@@ -1195,11 +2317,142 @@ mod tests {
         request = request.with_package_config(
             "fake_package".to_string(),
             ReleaseConfig {
-                publish: PublishConfig { enabled: true },
+                publish: PublishConfig::enabled(true),
                 ..Default::default()
             },
         );
 
         assert!(request.check_publish_fields().is_err());
     }
+
+    #[test]
+    fn packaging_args_respect_package_config() {
+        let metadata = fake_metadata();
+        let package = metadata.packages[0].clone();
+        let request = ReleaseRequest::new(metadata).with_package_config(
+            package.name.to_string(),
+            ReleaseConfig::default()
+                .with_no_verify(true)
+                .with_all_features(true),
+        );
+        let args = packaging_args(&package, &request, None, "a,b");
+        assert!(args.contains(&"--no-verify"));
+        assert!(args.contains(&"--all-features"));
+        assert!(!args.contains(&"--allow-dirty"));
+        assert!(args.windows(2).any(|w| w == ["--features", "a,b"]));
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--package", package.name.as_str()])
+        );
+    }
+
+    #[test]
+    fn registry_config_overrides_package_config() {
+        let metadata = fake_metadata();
+        let package = metadata.packages[0].clone();
+        let request = ReleaseRequest::new(metadata)
+            .with_publish_timeout(Duration::from_secs(60))
+            .with_registry_config(
+                "my_registry",
+                RegistryConfig::default()
+                    .with_no_verify(true)
+                    .with_publish_timeout(Duration::from_secs(10)),
+            );
+        let args = packaging_args(&package, &request, Some("my_registry"), "");
+        assert!(args.contains(&"--no-verify"));
+        assert!(args.windows(2).any(|w| w == ["--registry", "my_registry"]));
+        assert!(!packaging_args(&package, &request, None, "").contains(&"--no-verify"));
+        assert_eq!(
+            request.publish_timeout(Some("my_registry")),
+            Duration::from_secs(10)
+        );
+        assert_eq!(request.publish_timeout(None), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn registry_token_is_read_from_registry_config() {
+        let temp_dir = crate::fs_utils::Utf8TempDir::new().unwrap();
+        let token_path = temp_dir.path().join("token");
+        fs_err::write(&token_path, "file_token").unwrap();
+        let request = ReleaseRequest::new(fake_metadata())
+            .with_registry("other_registry")
+            .with_token(SecretString::from("request_token"))
+            .with_registry_config(
+                "my_registry",
+                RegistryConfig::default().with_token(crate::RegistryTokenSource::File(token_path)),
+            );
+        let token = request.find_registry_token(Some("my_registry")).unwrap();
+        assert_eq!(token.unwrap().expose_secret(), "file_token");
+        let token = request.find_registry_token(Some("other_registry")).unwrap();
+        assert_eq!(token.unwrap().expose_secret(), "request_token");
+    }
+
+    #[test]
+    fn release_assets_are_rendered_and_matched() {
+        let temp_dir = crate::fs_utils::Utf8TempDir::new().unwrap();
+        let dist = temp_dir.path().join("dist");
+        fs_err::create_dir_all(&dist).unwrap();
+        for file in [
+            "my_package-0.1.0-linux.tar.gz",
+            "my_package-0.1.0-macos.tar.gz",
+            "other.txt",
+        ] {
+            fs_err::write(dist.join(file), "").unwrap();
+        }
+        let mut package: Package = fake_package::FakePackage::new("my_package").into();
+        package.version = Version::new(0, 1, 0);
+        let config = GitReleaseConfig::default().set_assets(vec![
+            "dist/{{ package }}-{{ version }}-*.tar.gz".to_string(),
+        ]);
+
+        let assets = release_assets(temp_dir.path(), &package, &config).unwrap();
+        assert_eq!(
+            assets,
+            vec![
+                dist.join("my_package-0.1.0-linux.tar.gz"),
+                dist.join("my_package-0.1.0-macos.tar.gz"),
+            ]
+        );
+
+        let config = GitReleaseConfig::default().set_assets(vec!["dist/*.zip".to_string()]);
+        let error = release_assets(temp_dir.path(), &package, &config).unwrap_err();
+        assert!(error.to_string().contains("doesn't match any file"));
+    }
+
+    #[test]
+    fn git_tag_message_is_rendered() {
+        let package: Package = fake_package::FakePackage::new("my_package").into();
+        let default_message =
+            git_tag_message(&GitTagConfig::default(), &package, "my changes").unwrap();
+        assert_eq!(
+            default_message,
+            format!(
+                "chore: Release package my_package version {}",
+                package.version
+            )
+        );
+
+        let config = GitTagConfig::default()
+            .set_message_template(Some("{{ package }}\n\n{{ changelog }}".to_string()));
+        let message = git_tag_message(&config, &package, "### Added\n- feature").unwrap();
+        assert_eq!(message, "my_package\n\n### Added\n- feature");
+    }
+
+    #[test]
+    fn all_failed_packages_of_a_level_are_reported() {
+        let a: Package = fake_package::FakePackage::new("a").into();
+        let b: Package = fake_package::FakePackage::new("b").into();
+        assert!(level_failure(vec![]).is_ok());
+        let error = level_failure(vec![(&a, anyhow::anyhow!("upload failed"))]).unwrap_err();
+        assert_eq!(error.to_string(), "upload failed");
+        let error = level_failure(vec![
+            (&a, anyhow::anyhow!("upload failed")),
+            (&b, anyhow::anyhow!("tag failed")),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to release the packages a, b. Fix the errors above and run the release again with `--resume`"
+        );
+    }
 }
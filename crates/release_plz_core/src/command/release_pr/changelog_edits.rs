@@ -0,0 +1,226 @@
+//! Keep the edits that maintainers push to the changelogs of the release PR
+//! when release-plz regenerates the release PR branch.
+
+use anyhow::Context as _;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use git_cmd::Repo;
+use tracing::{debug, info};
+
+use crate::git::forge::PrCommit;
+
+/// Changelogs of the working tree, merged with the edits of the maintainers.
+#[derive(Debug)]
+pub(super) struct ChangelogEdits {
+    /// Last commit of the maintainers.
+    /// Its message and author are reused for the commit containing the edits.
+    last_commit: String,
+    /// Merged content of the edited changelogs, by path relative to the repository root.
+    files: Vec<(Utf8PathBuf, String)>,
+}
+
+/// Three-way merge of the changelog edits pushed to the release PR branch
+/// with the regenerated changelogs of the working tree.
+/// The edits are the changes of the commits that come after the first release-plz commit.
+///
+/// Returns [`None`] if the edits can't be kept, i.e. if the commits change
+/// files other than `changelog_paths` or if the merge conflicts.
+pub(super) fn merge_changelog_edits(
+    repo: &Repo,
+    pr_branch: &str,
+    pr_commits: &[PrCommit],
+    changelog_paths: &[Utf8PathBuf],
+) -> anyhow::Result<Option<ChangelogEdits>> {
+    let (Some(release_plz_commit), Some(last_commit)) = (pr_commits.first(), pr_commits.last())
+    else {
+        return Ok(None);
+    };
+    repo.fetch(pr_branch)
+        .with_context(|| format!("can't fetch branch {pr_branch}"))?;
+    let changed_files = repo.git(&[
+        "diff",
+        "--name-only",
+        &release_plz_commit.sha,
+        &last_commit.sha,
+    ])?;
+    let changed_files: Vec<Utf8PathBuf> = changed_files.lines().map(Utf8PathBuf::from).collect();
+    if let Some(file) = changed_files
+        .iter()
+        .find(|file| !changelog_paths.contains(file))
+    {
+        debug!("the release PR contains changes to {file}, which isn't a changelog");
+        return Ok(None);
+    }
+
+    let tmp_dir = tempfile::tempdir().context("can't create temporary directory")?;
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).context("invalid temporary directory")?;
+    let base_dir = tmp_dir.join("base");
+    let edited_dir = tmp_dir.join("edited");
+    restore_files(repo, &base_dir, &release_plz_commit.sha, &changed_files)?;
+    restore_files(repo, &edited_dir, &last_commit.sha, &changed_files)?;
+
+    let mut files = vec![];
+    for file in changed_files {
+        let current = repo.directory().join(&file);
+        let base = base_dir.join(&file);
+        let edited = edited_dir.join(&file);
+        if !current.exists() || !base.exists() || !edited.exists() {
+            debug!("{file} was added or removed in the release PR");
+            return Ok(None);
+        }
+        // `git merge-file` writes the result in the first file, so we merge in a copy.
+        let merged = tmp_dir.join("merged");
+        fs_err::copy(&current, &merged)?;
+        if let Err(e) = repo.git(&[
+            "merge-file",
+            merged.as_str(),
+            base.as_str(),
+            edited.as_str(),
+        ]) {
+            info!("the edits of {file} conflict with the regenerated changelog: {e:?}");
+            return Ok(None);
+        }
+        let merged = fs_err::read_to_string(&merged)?;
+        files.push((file, merged));
+    }
+    Ok(Some(ChangelogEdits {
+        last_commit: last_commit.sha.clone(),
+        files,
+    }))
+}
+
+/// Write the files of the commit in the directory, without touching the git index.
+fn restore_files(
+    repo: &Repo,
+    dir: &Utf8Path,
+    commit: &str,
+    files: &[Utf8PathBuf],
+) -> anyhow::Result<()> {
+    fs_err::create_dir_all(dir)?;
+    let work_tree = format!("--work-tree={dir}");
+    let source = format!("--source={commit}");
+    let mut args = vec![work_tree.as_str(), "restore", source.as_str(), "--"];
+    args.extend(files.iter().map(|f| f.as_str()));
+    repo.git(&args)
+        .with_context(|| format!("can't read the files of commit {commit}"))?;
+    Ok(())
+}
+
+/// Commit the merged changelogs on top of the regenerated release PR commit.
+pub(super) fn commit_changelog_edits(repo: &Repo, edits: &ChangelogEdits) -> anyhow::Result<()> {
+    for (file, content) in &edits.files {
+        fs_err::write(repo.directory().join(file), content)?;
+    }
+    let files: Vec<&str> = edits.files.iter().map(|(file, _)| file.as_str()).collect();
+    repo.add(&files)?;
+    if repo.is_clean().is_ok() {
+        debug!("the regenerated changelogs already contain the edits");
+        return Ok(());
+    }
+    // Reuse the message and the author of the maintainer's commit, so that
+    // release-plz recognizes the edits in the next run.
+    repo.commit_signed_reusing_message(&edits.last_commit)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "CHANGELOG.md";
+
+    fn commit_changelog(repo: &Repo, content: &str, message: &str) -> PrCommit {
+        fs_err::write(repo.directory().join(CHANGELOG), content).unwrap();
+        repo.add_all_and_commit(message).unwrap();
+        PrCommit {
+            author: None,
+            sha: repo.current_commit_hash().unwrap(),
+        }
+    }
+
+    #[test]
+    fn changelog_edits_are_merged_with_regenerated_changelog() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repo::init(tmp.path());
+        repo.git(&["remote", "add", "origin", repo.directory().as_str()])
+            .unwrap();
+        repo.git(&["checkout", "-b", "release-plz-branch"]).unwrap();
+        let release_plz_commit = commit_changelog(
+            &repo,
+            "# Changelog\n\n## [0.1.1]\n\n- fix bug\n\n## [0.1.0]\n\n- initial release\n",
+            "chore: release",
+        );
+        let edit_commit = commit_changelog(
+            &repo,
+            "# Changelog\n\n## [0.1.1]\n\n- fix bug\n\n## [0.1.0]\n\n- first release\n",
+            "docs: edit changelog",
+        );
+        repo.checkout(repo.original_branch()).unwrap();
+        // The new commit of the default branch adds a new changelog entry.
+        let regenerated = "# Changelog\n\n## [0.1.1]\n\n- fix bug\n- add feature\n\n## [0.1.0]\n\n- initial release\n";
+        fs_err::write(repo.directory().join(CHANGELOG), regenerated).unwrap();
+
+        let edits = merge_changelog_edits(
+            &repo,
+            "release-plz-branch",
+            &[release_plz_commit, edit_commit],
+            &[Utf8PathBuf::from(CHANGELOG)],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            edits.files,
+            vec![(
+                Utf8PathBuf::from(CHANGELOG),
+                "# Changelog\n\n## [0.1.1]\n\n- fix bug\n- add feature\n\n## [0.1.0]\n\n- first release\n"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn changelog_edits_are_committed_with_the_author_of_the_edits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repo::init(tmp.path());
+        repo.git(&["remote", "add", "origin", repo.directory().as_str()])
+            .unwrap();
+        repo.git(&["checkout", "-b", "release-plz-branch"]).unwrap();
+        let release_plz_commit = commit_changelog(
+            &repo,
+            "## [0.1.1]\n\n- fix bug\n\n## [0.1.0]\n\n- initial release\n",
+            "chore: release",
+        );
+        repo.git(&["config", "user.name", "maintainer"]).unwrap();
+        let edit_commit = commit_changelog(
+            &repo,
+            "## [0.1.1]\n\n- fix bug\n\n## [0.1.0]\n\n- first release\n",
+            "docs: edit changelog",
+        );
+        repo.git(&["config", "user.name", "release-plz"]).unwrap();
+        repo.checkout(repo.original_branch()).unwrap();
+        fs_err::write(
+            repo.directory().join(CHANGELOG),
+            "## [0.1.1]\n\n- fix bug\n- add feature\n\n## [0.1.0]\n\n- initial release\n",
+        )
+        .unwrap();
+        let edits = merge_changelog_edits(
+            &repo,
+            "release-plz-branch",
+            &[release_plz_commit, edit_commit],
+            &[Utf8PathBuf::from(CHANGELOG)],
+        )
+        .unwrap()
+        .unwrap();
+        // The regenerated release PR commit.
+        repo.add_all_and_commit("chore: release").unwrap();
+
+        commit_changelog_edits(&repo, &edits).unwrap();
+
+        let last_commit = repo.git(&["log", "-1", "--format=%an%n%B"]).unwrap();
+        assert!(last_commit.starts_with("maintainer\ndocs: edit changelog\n"));
+        assert!(last_commit.contains("Signed-off-by: release-plz"));
+        assert_eq!(
+            fs_err::read_to_string(repo.directory().join(CHANGELOG)).unwrap(),
+            "## [0.1.1]\n\n- fix bug\n- add feature\n\n## [0.1.0]\n\n- first release\n"
+        );
+    }
+}
@@ -1,22 +1,33 @@
-use cargo_metadata::camino::Utf8Path;
+mod changelog_edits;
+
+use cargo_metadata::Package;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
 use cargo_metadata::semver::Version;
-use cargo_utils::CARGO_TOML;
+use cargo_utils::{CARGO_TOML, LocalManifest};
 use git_cmd::Repo;
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::Context;
+use chrono::Utc;
 use serde::Serialize;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 use url::Url;
 
+use crate::codeowners::CodeOwners;
 use crate::git::forge::{
-    ForgeType, GitClient, GitPr, PrEdit, contributors_from_commits, validate_labels,
+    ForgeType, GitClient, GitPr, MergeMethod, PrEdit, contributors_from_commits, validate_labels,
 };
 use crate::git::github_graphql;
-use crate::pr::{DEFAULT_BRANCH_PREFIX, OLD_BRANCH_PREFIX, Pr};
+use crate::metadata_lint::{is_published_to_crates_io, lint_metadata};
+use crate::milestone::{assign_pr_to_milestone, milestone_title};
+use crate::pr::{DEFAULT_BRANCH_PREFIX, OLD_BRANCH_PREFIX, Pr, PrReviewers, is_release_branch};
+use crate::release_freeze::active_freeze;
 use crate::{
-    PackagesUpdate, copy_to_temp_dir, new_manifest_dir_path, new_project_root,
+    ActiveFreeze, CRATES_IO_REGISTRY_NAME, FreezeWindow, PackagePath as _, PackagesUpdate,
+    copy_to_temp_dir, new_manifest_dir_path, new_project_root, next_versions,
     publishable_packages_from_manifest, root_repo_path_from_manifest_dir, update,
 };
+use changelog_edits::{ChangelogEdits, commit_changelog_edits, merge_changelog_edits};
 
 use super::update_request::UpdateRequest;
 
@@ -32,9 +43,42 @@ pub struct ReleasePrRequest {
     labels: Vec<String>,
     /// PR Branch Prefix
     branch_prefix: String,
+    /// If true, check the crates.io metadata of the packages before opening the release PR.
+    metadata_lint: bool,
+    /// Periods of time when the packages must not be published.
+    /// During a freeze, the release PR is marked as a draft.
+    freeze_windows: Vec<FreezeWindow>,
+    /// Tera template of the title of the milestone to assign to the release PR.
+    milestone: Option<String>,
+    /// How the packages are split in release PRs.
+    mode: ReleasePrMode,
+    /// If set, merge the release PR automatically with this method when the checks pass.
+    auto_merge: Option<MergeMethod>,
+    /// Reviewers and assignees of the release PR.
+    reviewers: PrReviewers,
+    /// Reviewers and assignees of the release PR, by package name.
+    /// They are added to the release PR when it updates the package.
+    package_reviewers: HashMap<String, PrReviewers>,
+    /// If true, request a review from the code owners of the updated packages.
+    reviewers_from_codeowners: bool,
     pub update_request: UpdateRequest,
 }
 
+/// How the packages to release are split in release PRs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReleasePrMode {
+    /// One release PR for all the packages.
+    #[default]
+    Workspace,
+    /// One release PR for each package.
+    /// Packages of the same version group share the release PR,
+    /// because they must have the same version.
+    Package,
+    /// One release PR for each version group.
+    /// Packages without a version group share one release PR.
+    VersionGroup,
+}
+
 impl ReleasePrRequest {
     pub fn new(update_request: UpdateRequest) -> Self {
         Self {
@@ -43,6 +87,14 @@ impl ReleasePrRequest {
             draft: false,
             labels: vec![],
             branch_prefix: DEFAULT_BRANCH_PREFIX.to_string(),
+            metadata_lint: true,
+            freeze_windows: vec![],
+            milestone: None,
+            mode: ReleasePrMode::default(),
+            auto_merge: None,
+            reviewers: PrReviewers::default(),
+            package_reviewers: HashMap::new(),
+            reviewers_from_codeowners: false,
             update_request,
         }
     }
@@ -73,6 +125,89 @@ impl ReleasePrRequest {
         }
         self
     }
+
+    pub fn with_metadata_lint(mut self, metadata_lint: bool) -> Self {
+        self.metadata_lint = metadata_lint;
+        self
+    }
+
+    pub fn with_freeze_windows(mut self, freeze_windows: Vec<FreezeWindow>) -> Self {
+        self.freeze_windows = freeze_windows;
+        self
+    }
+
+    pub fn with_milestone(mut self, milestone_template: impl Into<String>) -> Self {
+        self.milestone = Some(milestone_template.into());
+        self
+    }
+
+    pub fn with_mode(mut self, mode: ReleasePrMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_auto_merge(mut self, merge_method: MergeMethod) -> Self {
+        self.auto_merge = Some(merge_method);
+        self
+    }
+
+    pub fn with_reviewers(mut self, reviewers: PrReviewers) -> Self {
+        self.reviewers = reviewers;
+        self
+    }
+
+    /// Add reviewers and assignees to the release PR when it updates the package.
+    pub fn with_package_reviewers(
+        mut self,
+        package: impl Into<String>,
+        reviewers: PrReviewers,
+    ) -> Self {
+        self.package_reviewers.insert(package.into(), reviewers);
+        self
+    }
+
+    /// Request a review from the owners of the directories of the updated packages,
+    /// according to the `CODEOWNERS` file of the repository.
+    pub fn with_reviewers_from_codeowners(mut self, reviewers_from_codeowners: bool) -> Self {
+        self.reviewers_from_codeowners = reviewers_from_codeowners;
+        self
+    }
+
+    /// Whether the PR body shows the package contents.
+    /// Listing the package contents runs `cargo package --list` for each package,
+    /// so we skip it if the PR body doesn't need it.
+    fn renders_package_contents(&self) -> bool {
+        self.pr_body_template
+            .as_deref()
+            .is_none_or(|template| template.contains("package_contents"))
+    }
+
+    /// Reviewers and assignees of the release PR that updates the given packages.
+    fn pr_reviewers(
+        &self,
+        packages_to_update: &PackagesUpdate,
+        repo_root: &Utf8Path,
+    ) -> anyhow::Result<PrReviewers> {
+        let mut reviewers = self.reviewers.clone();
+        let code_owners = if self.reviewers_from_codeowners {
+            CodeOwners::from_repo(repo_root)?
+        } else {
+            None
+        };
+        for (package, _) in packages_to_update.updates() {
+            if let Some(package_reviewers) = self.package_reviewers.get(package.name.as_str()) {
+                reviewers.extend(package_reviewers);
+            }
+            if let Some(code_owners) = &code_owners {
+                let package_dir = package
+                    .package_path()?
+                    .strip_prefix(repo_root)
+                    .with_context(|| format!("package {} isn't in the repository", package.name))?;
+                reviewers.extend(&code_owners.reviewers(package_dir));
+            }
+        }
+        Ok(reviewers)
+    }
 }
 
 /// Release pull request that release-plz opened/updated.
@@ -89,6 +224,8 @@ pub struct ReleasePr {
     pub number: u64,
     /// Releases of the packages that are going to be published.
     pub releases: Vec<PrPackageRelease>,
+    /// Whether release-plz enabled auto-merge for the PR.
+    pub auto_merge: bool,
 }
 
 impl ReleasePr {
@@ -99,6 +236,7 @@ impl ReleasePr {
             html_url: git_pr.html_url.clone(),
             number: git_pr.number,
             releases: vec![],
+            auto_merge: false,
         }
     }
 }
@@ -112,14 +250,98 @@ pub struct PrPackageRelease {
     version: Version,
 }
 
-/// Open a pull request with the next packages versions of a local rust project
-/// Returns:
-/// - [`ReleasePr`] if release-plz opened or updated a PR.
-/// - [`None`] if release-plz didn't open any pr. This happens when all packages
-///   are up-to-date.
+/// Open pull requests with the next packages versions of a local rust project.
+/// Returns the release PRs that release-plz opened or updated.
+/// The vector is empty when all packages are up-to-date.
 #[instrument(skip_all)]
-pub async fn release_pr(input: &ReleasePrRequest) -> anyhow::Result<Option<ReleasePr>> {
-    let manifest_dir = input.update_request.local_manifest_dir()?;
+pub async fn release_pr(input: &ReleasePrRequest) -> anyhow::Result<Vec<ReleasePr>> {
+    validate_labels(&input.labels)?;
+    let git_client = input
+        .update_request
+        .git_client()?
+        .context("can't find git client")?;
+    let freeze = active_freeze(&input.freeze_windows, Utc::now());
+    if input.mode == ReleasePrMode::Workspace {
+        let release_pr = prepare_release_pr(
+            input,
+            &git_client,
+            input.update_request.clone(),
+            input.branch_prefix.clone(),
+            PrGroup::Workspace,
+            freeze,
+        )
+        .await?;
+        return Ok(release_pr.into_iter().collect());
+    }
+
+    let (packages_to_update, _temp_repository) = next_versions(&input.update_request)
+        .await
+        .context("failed to determine next versions")?;
+    let groups = release_pr_groups(input, &packages_to_update)?;
+    debug!("release PR groups: {groups:?}");
+    let opened_release_prs = all_opened_release_prs(&git_client, &input.branch_prefix).await?;
+    let mut release_prs = vec![];
+    let mut groups_branch_prefixes = vec![];
+    for (group, packages) in groups {
+        let update_request = input.update_request.clone().with_packages(packages);
+        let branch_prefix = group_branch_prefix(&input.branch_prefix, &group);
+        let release_pr = prepare_release_pr(
+            input,
+            &git_client,
+            update_request,
+            branch_prefix.clone(),
+            PrGroup::Packages,
+            freeze.clone(),
+        )
+        .await
+        .with_context(|| format!("failed to open the release PR of {group}"))?;
+        if let Some(release_pr) = release_pr {
+            release_prs.push(release_pr);
+            groups_branch_prefixes.push(branch_prefix);
+        }
+    }
+
+    // Close the PRs of the packages that don't need a release anymore.
+    for pr in opened_release_prs {
+        let is_stale = !groups_branch_prefixes
+            .iter()
+            .any(|prefix| is_release_branch(pr.branch(), prefix));
+        if is_stale {
+            info!(
+                "closing pr {} because its packages don't need a release",
+                pr.html_url
+            );
+            git_client
+                .close_pr(pr.number)
+                .await
+                .context("cannot close old release-plz prs")?;
+        }
+    }
+    Ok(release_prs)
+}
+
+/// Packages included in a release PR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrGroup {
+    /// All the packages of the workspace.
+    Workspace,
+    /// A package or a version group.
+    /// The branch prefix contains the name of the group.
+    Packages,
+}
+
+/// Update the packages in a copy of the project and open or update the release PR
+/// with the changes.
+/// Returns [`None`] if the packages are up-to-date.
+async fn prepare_release_pr(
+    input: &ReleasePrRequest,
+    git_client: &GitClient,
+    update_request: UpdateRequest,
+    branch_prefix: String,
+    group: PrGroup,
+    freeze: Option<ActiveFreeze>,
+) -> anyhow::Result<Option<ReleasePr>> {
+    let manifest_dir = update_request.local_manifest_dir()?;
     let original_project_root = root_repo_path_from_manifest_dir(manifest_dir)?;
     let tmp_project_root_parent = copy_to_temp_dir(&original_project_root)?;
     let tmp_project_manifest_dir = new_manifest_dir_path(
@@ -128,46 +350,228 @@ pub async fn release_pr(input: &ReleasePrRequest) -> anyhow::Result<Option<Relea
         tmp_project_root_parent.path(),
     )?;
 
-    validate_labels(&input.labels)?;
     let tmp_project_root =
         new_project_root(&original_project_root, tmp_project_root_parent.path())?;
 
     let local_manifest = tmp_project_manifest_dir.join(CARGO_TOML);
-    let new_update_request = input
-        .update_request
-        .clone()
+    let new_update_request = update_request
         .set_local_manifest(&local_manifest)
-        .context("can't find temporary project")?;
+        .context("can't find temporary project")?
+        .with_package_contents_diff(input.renders_package_contents());
     let (packages_to_update, _temp_repository) = update(&new_update_request)
         .await
         .context("failed to update packages")?;
-    let git_client = input
-        .update_request
-        .git_client()?
-        .context("can't find git client")?;
-    if !packages_to_update.updates().is_empty() {
-        let repo = Repo::new(tmp_project_root)?;
-        let there_are_commits_to_push = repo.is_clean().is_err();
-        if there_are_commits_to_push {
-            let pr = open_or_update_release_pr(
-                &local_manifest,
-                &packages_to_update,
-                &git_client,
-                &repo,
-                ReleasePrOptions {
-                    draft: input.draft,
-                    pr_name: input.pr_name_template.clone(),
-                    pr_body: input.pr_body_template.clone(),
-                    pr_labels: input.labels.clone(),
-                    pr_branch_prefix: input.branch_prefix.clone(),
-                },
-            )
-            .await?;
-            return Ok(Some(pr));
+    if input.metadata_lint {
+        lint_updated_packages(input, &packages_to_update)
+            .context("metadata lint failed. The release PR wasn't opened")?;
+    }
+    if packages_to_update.updates().is_empty() {
+        return Ok(None);
+    }
+    let repo = Repo::new(tmp_project_root)?;
+    let there_are_commits_to_push = repo.is_clean().is_err();
+    if !there_are_commits_to_push {
+        return Ok(None);
+    }
+    let changelog_paths = changelog_paths(&new_update_request, &packages_to_update, &repo);
+    let pr = open_or_update_release_pr(
+        &local_manifest,
+        &packages_to_update,
+        git_client,
+        &repo,
+        ReleasePrOptions {
+            draft: input.draft,
+            pr_name: input.pr_name_template.clone(),
+            pr_body: input.pr_body_template.clone(),
+            pr_labels: input.labels.clone(),
+            pr_reviewers: input.pr_reviewers(&packages_to_update, repo.directory())?,
+            pr_branch_prefix: branch_prefix,
+            group,
+            changelog_paths,
+            auto_merge: input.auto_merge,
+            freeze,
+        },
+    )
+    .await?;
+    if let Some(template) = &input.milestone {
+        // The PR is already open, so a missing milestone shouldn't fail the command.
+        if let Err(e) =
+            set_release_pr_milestone(git_client, &pr, template, &packages_to_update).await
+        {
+            warn!("can't set the milestone of the release PR: {e:?}");
+        }
+    }
+    Ok(Some(pr))
+}
+
+/// Paths of the changelogs updated by release-plz, relative to the repository root.
+fn changelog_paths(
+    update_request: &UpdateRequest,
+    packages_to_update: &PackagesUpdate,
+    repo: &Repo,
+) -> Vec<Utf8PathBuf> {
+    let mut paths = vec![];
+    for (package, update) in packages_to_update.updates() {
+        if update.changelog.is_none() {
+            continue;
+        }
+        let changelog_path = update_request.changelog_path(package);
+        if let Ok(path) = changelog_path.strip_prefix(repo.directory())
+            && !paths.iter().any(|p: &Utf8PathBuf| p == path)
+        {
+            paths.push(path.to_path_buf());
         }
     }
+    paths
+}
 
-    Ok(None)
+/// Split the packages to release in groups. Each group has its own release PR.
+/// Returns the package names of each group, by group name.
+fn release_pr_groups(
+    input: &ReleasePrRequest,
+    packages_to_update: &PackagesUpdate,
+) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (package, _) in packages_to_update.updates() {
+        let manifest_path = package.package_path()?.join(CARGO_TOML);
+        let version_is_inherited = LocalManifest::try_new(&manifest_path)
+            .with_context(|| format!("can't read manifest {manifest_path}"))?
+            .version_is_inherited();
+        let version_group = input
+            .update_request
+            .get_package_config(&package.name)
+            .version_group;
+        let group = group_name(
+            &package.name,
+            version_group,
+            version_is_inherited,
+            input.mode,
+        );
+        groups
+            .entry(group)
+            .or_default()
+            .push(package.name.to_string());
+    }
+    Ok(groups)
+}
+
+/// Group of the packages that inherit the workspace version.
+const WORKSPACE_GROUP: &str = "workspace";
+/// Group of the packages without a version group, in [`ReleasePrMode::VersionGroup`].
+const UNGROUPED_GROUP: &str = "ungrouped";
+
+/// Name of the release PR group of the package.
+/// Packages that inherit the workspace version or that belong to the same
+/// version group are always released together, because they share the same version.
+/// Splitting them would let `next_versions` see only part of the group.
+/// The other packages have their own group in [`ReleasePrMode::Package`]
+/// and share a group in [`ReleasePrMode::VersionGroup`].
+fn group_name(
+    package_name: &str,
+    version_group: Option<String>,
+    version_is_inherited: bool,
+    mode: ReleasePrMode,
+) -> String {
+    if version_is_inherited {
+        return WORKSPACE_GROUP.to_string();
+    }
+    match (version_group, mode) {
+        (Some(version_group), _) => version_group,
+        (None, ReleasePrMode::VersionGroup) => UNGROUPED_GROUP.to_string(),
+        (None, ReleasePrMode::Package | ReleasePrMode::Workspace) => package_name.to_string(),
+    }
+}
+
+fn group_branch_prefix(branch_prefix: &str, group: &str) -> String {
+    format!("{branch_prefix}{group}-")
+}
+
+/// Release PRs opened by release-plz, including the ones with the old branch prefix.
+async fn all_opened_release_prs(
+    git_client: &GitClient,
+    branch_prefix: &str,
+) -> anyhow::Result<Vec<GitPr>> {
+    let mut opened_release_prs = git_client
+        .opened_prs(branch_prefix)
+        .await
+        .context("cannot get opened release-plz prs")?;
+    let old_release_prs = git_client
+        .opened_prs(OLD_BRANCH_PREFIX)
+        .await
+        .context("cannot get opened release-plz prs")?;
+    opened_release_prs.extend(old_release_prs);
+    Ok(opened_release_prs)
+}
+
+/// Assign the release PR to the milestone of the upcoming version.
+/// A PR can only have one milestone, so if the packages have different milestones,
+/// the milestone of the first package is used.
+async fn set_release_pr_milestone(
+    git_client: &GitClient,
+    pr: &ReleasePr,
+    template: &str,
+    packages_to_update: &PackagesUpdate,
+) -> anyhow::Result<()> {
+    let mut titles = vec![];
+    for (package, update) in packages_to_update.updates() {
+        let title = milestone_title(template, &package.name, &update.version.to_string())?;
+        if !titles.contains(&title) {
+            titles.push(title);
+        }
+    }
+    let Some(title) = titles.first() else {
+        return Ok(());
+    };
+    if titles.len() > 1 {
+        warn!(
+            "the packages of the release PR have different milestones: {titles:?}. Using milestone {title}"
+        );
+    }
+    assign_pr_to_milestone(git_client, pr.number, title).await
+}
+
+/// Part of the freeze banner that tells that release-plz marked the PR as draft.
+const FREEZE_DRAFT_NOTE: &str = "This PR is a draft because of a";
+
+/// Text shown at the top of the release PR body during a release freeze.
+fn freeze_banner(freeze: &ActiveFreeze) -> String {
+    format!(
+        "> [!WARNING]
+> {FREEZE_DRAFT_NOTE} {freeze}.
+> `release-plz release` won't publish the packages until the freeze ends.
+"
+    )
+}
+
+/// Whether release-plz marked the PR as draft because of a release freeze that ended.
+/// PRs that are drafts because of the user (e.g. with `pr_draft`) stay drafts.
+fn is_freeze_draft_to_undo(opened_pr: &GitPr, new_pr: &Pr, is_frozen: bool) -> bool {
+    let is_drafted_by_freeze = opened_pr
+        .body
+        .as_deref()
+        .is_some_and(|body| body.contains(FREEZE_DRAFT_NOTE));
+    !is_frozen && opened_pr.draft && !new_pr.draft && is_drafted_by_freeze
+}
+
+/// Lint the crates.io metadata of the packages that the release PR is going to release.
+fn lint_updated_packages(
+    input: &ReleasePrRequest,
+    packages_to_update: &PackagesUpdate,
+) -> anyhow::Result<()> {
+    if input
+        .update_request
+        .registry()
+        .is_some_and(|registry| registry != CRATES_IO_REGISTRY_NAME)
+    {
+        return Ok(());
+    }
+    let packages: Vec<&Package> = packages_to_update
+        .updates()
+        .iter()
+        .map(|(package, _)| package)
+        .filter(|package| is_published_to_crates_io(package))
+        .collect();
+    lint_metadata(&packages)
 }
 
 struct ReleasePrOptions {
@@ -175,7 +579,14 @@ struct ReleasePrOptions {
     pr_name: Option<String>,
     pr_body: Option<String>,
     pr_labels: Vec<String>,
+    pr_reviewers: PrReviewers,
     pr_branch_prefix: String,
+    group: PrGroup,
+    /// Changelogs updated by the release PR, relative to the repository root.
+    changelog_paths: Vec<Utf8PathBuf>,
+    auto_merge: Option<MergeMethod>,
+    /// Release freeze that is active now.
+    freeze: Option<ActiveFreeze>,
 }
 
 async fn open_or_update_release_pr(
@@ -190,10 +601,15 @@ async fn open_or_update_release_pr(
         .await
         .context("cannot get opened release-plz prs")?;
 
-    // Check if there are opened release-plz prs with the old prefix.
-    // This ensures retro-compatibility with the release-plz versions.
-    // TODO: Remove this check on release-plz v0.4.0.
-    if opened_release_prs.is_empty() {
+    if release_pr_options.group == PrGroup::Packages {
+        // The prefix of a group can be the prefix of another group,
+        // e.g. `release-plz-my-` and `release-plz-my-package-`.
+        opened_release_prs
+            .retain(|pr| is_release_branch(pr.branch(), &release_pr_options.pr_branch_prefix));
+    } else if opened_release_prs.is_empty() {
+        // Check if there are opened release-plz prs with the old prefix.
+        // This ensures retro-compatibility with the release-plz versions.
+        // TODO: Remove this check on release-plz v0.4.0.
         opened_release_prs = git_client
             .opened_prs(OLD_BRANCH_PREFIX)
             .await
@@ -212,7 +628,7 @@ async fn open_or_update_release_pr(
     let new_pr = {
         let project_contains_multiple_pub_packages =
             publishable_packages_from_manifest(local_manifest)?.len() > 1;
-        Pr::new(
+        let pr = Pr::new(
             repo.original_branch(),
             packages_to_update,
             project_contains_multiple_pub_packages,
@@ -222,7 +638,19 @@ async fn open_or_update_release_pr(
         )?
         .mark_as_draft(release_pr_options.draft)
         .with_labels(release_pr_options.pr_labels)
+        .with_reviewers(release_pr_options.pr_reviewers);
+        match &release_pr_options.freeze {
+            Some(freeze) => {
+                info!("{freeze}: marking the release PR as draft");
+                let title = git_client.draft_title(&pr.title);
+                let mut pr = pr.mark_as_draft(true).with_banner(&freeze_banner(freeze));
+                pr.title = title;
+                pr
+            }
+            None => pr,
+        }
     };
+    let is_frozen = release_pr_options.freeze.is_some();
     let release_pr = match opened_release_prs.first() {
         Some(opened_pr) => {
             handle_opened_pr(
@@ -231,12 +659,37 @@ async fn open_or_update_release_pr(
                 repo,
                 &new_pr,
                 &release_pr_options.pr_branch_prefix,
+                &release_pr_options.changelog_paths,
+                is_frozen,
             )
             .await
         }
         None => create_pr(git_client, repo, &new_pr).await,
     }?;
+    let auto_merge = match release_pr_options.auto_merge {
+        Some(_) if new_pr.draft => {
+            info!("the release PR is a draft, so release-plz doesn't enable auto-merge");
+            false
+        }
+        Some(merge_method) => {
+            match git_client
+                .enable_auto_merge(release_pr.number, merge_method)
+                .await
+            {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!(
+                        "cannot enable auto-merge for release pr {}: {e:?}",
+                        release_pr.number
+                    );
+                    false
+                }
+            }
+        }
+        None => false,
+    };
     let release_pr = ReleasePr {
+        auto_merge,
         releases: packages_to_update
             .updates()
             .iter()
@@ -256,51 +709,81 @@ async fn handle_opened_pr(
     repo: &Repo,
     new_pr: &Pr,
     branch_prefix: &str,
+    changelog_paths: &[Utf8PathBuf],
+    is_frozen: bool,
 ) -> Result<ReleasePr, anyhow::Error> {
     let pr_commits = git_client
         .pr_commits(opened_pr.number)
         .await
         .context("cannot get commits of release-plz pr")?;
     let pr_contributors = contributors_from_commits(&pr_commits);
-    Ok(if pr_contributors.is_empty() {
-        // There are no contributors, so we can force-push
-        // in this PR, because we don't care about the git history.
-        match update_pr(
+    // If the contributors only edited the changelogs, we keep their edits.
+    let changelog_edits = if pr_contributors.is_empty() {
+        None
+    } else {
+        match merge_changelog_edits(repo, opened_pr.branch(), &pr_commits, changelog_paths) {
+            Ok(Some(edits)) => Some(edits),
+            Ok(None) => {
+                // There's a contributor, so we don't want to force-push in this PR.
+                // We close it because we want to save the contributor's work.
+                // TODO improvement: check how many lines the commit added, if no lines (for example a merge to update the branch),
+                //      then don't count it as a contributor.
+                info!("closing pr {} to preserve git history", opened_pr.html_url);
+                return close_and_create_pr(git_client, opened_pr, repo, new_pr).await;
+            }
+            Err(e) => {
+                warn!(
+                    "cannot keep the changelog edits of release pr {}: {e:?}. I'm closing the old release pr and opening a new one",
+                    opened_pr.number
+                );
+                return close_and_create_pr(git_client, opened_pr, repo, new_pr).await;
+            }
+        }
+    };
+    // Force-push in this PR: either nobody changed it or we carry their changelog edits over.
+    // If the update fails before the force-push, the closed PR still contains the edits.
+    let update_result = async {
+        push_pr_branch(
             git_client,
             opened_pr,
             pr_commits.len(),
             repo,
-            new_pr,
             branch_prefix,
+            changelog_edits.as_ref(),
         )
-        .await
-        {
-            Ok(()) => ReleasePr::new(opened_pr, new_pr.base_branch.clone()),
-            Err(e) => {
-                tracing::error!(
-                    "cannot update release pr {}: {:?}. I'm closing the old release pr and opening a new one",
-                    opened_pr.number,
-                    e
-                );
-                git_client
-                    .close_pr(opened_pr.number)
-                    .await
-                    .context("cannot close old release-plz prs")?;
-                create_pr(git_client, repo, new_pr).await?
+        .await?;
+        update_pr(git_client, opened_pr, new_pr, is_frozen).await
+    }
+    .await;
+    match update_result {
+        Ok(()) => {
+            if changelog_edits.is_some() {
+                info!("kept the changelog edits of pr {}", opened_pr.html_url);
             }
+            Ok(ReleasePr::new(opened_pr, new_pr.base_branch.clone()))
         }
-    } else {
-        // There's a contributor, so we don't want to force-push in this PR.
-        // We close it because we want to save the contributor's work.
-        // TODO improvement: check how many lines the commit added, if no lines (for example a merge to update the branch),
-        //      then don't count it as a contributor.
-        info!("closing pr {} to preserve git history", opened_pr.html_url);
-        git_client
-            .close_pr(opened_pr.number)
-            .await
-            .context("cannot close old release-plz prs")?;
-        create_pr(git_client, repo, new_pr).await?
-    })
+        Err(e) => {
+            tracing::error!(
+                "cannot update release pr {}: {:?}. I'm closing the old release pr and opening a new one",
+                opened_pr.number,
+                e
+            );
+            close_and_create_pr(git_client, opened_pr, repo, new_pr).await
+        }
+    }
+}
+
+async fn close_and_create_pr(
+    git_client: &GitClient,
+    opened_pr: &GitPr,
+    repo: &Repo,
+    new_pr: &Pr,
+) -> anyhow::Result<ReleasePr> {
+    git_client
+        .close_pr(opened_pr.number)
+        .await
+        .context("cannot close old release-plz prs")?;
+    create_pr(git_client, repo, new_pr).await
 }
 
 async fn create_pr(git_client: &GitClient, repo: &Repo, pr: &Pr) -> anyhow::Result<ReleasePr> {
@@ -316,13 +799,14 @@ async fn create_pr(git_client: &GitClient, repo: &Repo, pr: &Pr) -> anyhow::Resu
     Ok(ReleasePr::new(&git_pr, pr.base_branch.clone()))
 }
 
-async fn update_pr(
+/// Regenerate the branch of the opened PR and force-push it.
+async fn push_pr_branch(
     git_client: &GitClient,
     opened_pr: &GitPr,
     commits_number: usize,
     repository: &Repo,
-    new_pr: &Pr,
     branch_prefix: &str,
+    changelog_edits: Option<&ChangelogEdits>,
 ) -> anyhow::Result<()> {
     update_pr_branch(commits_number, opened_pr, repository, branch_prefix).with_context(|| {
         format!(
@@ -331,10 +815,19 @@ async fn update_pr(
         )
     })?;
     if matches!(git_client.forge, ForgeType::Github) {
-        github_force_push(git_client, opened_pr, repository).await?;
+        github_force_push(git_client, opened_pr, repository, changelog_edits).await?;
     } else {
-        force_push(opened_pr, repository)?;
+        force_push(opened_pr, repository, changelog_edits)?;
     }
+    Ok(())
+}
+
+async fn update_pr(
+    git_client: &GitClient,
+    opened_pr: &GitPr,
+    new_pr: &Pr,
+    is_frozen: bool,
+) -> anyhow::Result<()> {
     let pr_edit = {
         let mut pr_edit = PrEdit::new();
         if opened_pr.title != new_pr.title {
@@ -348,11 +841,41 @@ async fn update_pr(
     if pr_edit.contains_edit() {
         git_client.edit_pr(opened_pr.number, pr_edit).await?;
     }
+    if is_frozen && !opened_pr.draft && matches!(git_client.forge, ForgeType::Github) {
+        // GitLab and Gitea mark the PR as draft with the title prefix.
+        let node_id = opened_pr
+            .node_id
+            .as_deref()
+            .context("the GitHub PR doesn't have a node ID")?;
+        github_graphql::convert_pr_to_draft(git_client, node_id)
+            .await
+            .context("cannot mark the release PR as draft")?;
+    }
+    if is_freeze_draft_to_undo(opened_pr, new_pr, is_frozen)
+        && matches!(git_client.forge, ForgeType::Github)
+    {
+        // GitLab and Gitea mark the PR as ready when the title loses the draft prefix.
+        let node_id = opened_pr
+            .node_id
+            .as_deref()
+            .context("the GitHub PR doesn't have a node ID")?;
+        github_graphql::mark_pr_ready_for_review(git_client, node_id)
+            .await
+            .context("cannot mark the release PR as ready for review")?;
+        info!(
+            "release freeze ended: marked pr {} as ready for review",
+            opened_pr.html_url
+        );
+    }
     if opened_pr.label_names() != new_pr.labels {
         git_client
             .add_labels(&new_pr.labels, opened_pr.number)
             .await?;
     }
+    git_client
+        .add_reviewers(opened_pr, &new_pr.reviewers)
+        .await
+        .context("failed to add reviewers and assignees")?;
     info!("updated pr {}", opened_pr.html_url);
     Ok(())
 }
@@ -402,18 +925,37 @@ fn reset_branch(
 
     repository.fetch(repository.original_branch())?;
 
-    // Update PR branch with latest changes from the default branch.
+    rebase_on_original_branch(repository)
+}
+
+/// Update the current branch with the latest changes from the default branch.
+/// If the rebase conflicts, rebuild the branch from the default branch:
+/// the release PR content is generated by release-plz, so it will be recomputed anyway.
+fn rebase_on_original_branch(repository: &Repo) -> anyhow::Result<()> {
     if let Err(e) = repository.git(&["rebase", repository.original_branch()]) {
         // Get back to the state before "git rebase" to clean the merge conflict.
         repository.git(&["rebase ", "--abort"])?;
-        return Err(e.context("cannot rebase from default branch"));
+        warn!(
+            "cannot rebase release PR branch from `{}`: {e:?}. Regenerating the branch",
+            repository.original_branch()
+        );
+        repository
+            .git(&["reset", "--hard", repository.original_branch()])
+            .context("cannot reset the release PR branch to the default branch")?;
     }
-
     Ok(())
 }
 
-fn force_push(pr: &GitPr, repository: &Repo) -> anyhow::Result<()> {
+fn force_push(
+    pr: &GitPr,
+    repository: &Repo,
+    changelog_edits: Option<&ChangelogEdits>,
+) -> anyhow::Result<()> {
     add_changes_and_commit(repository, &pr.title)?;
+    if let Some(edits) = changelog_edits {
+        commit_changelog_edits(repository, edits)
+            .context("cannot commit the changelog edits of the release PR")?;
+    }
     repository.force_push(pr.branch())?;
     Ok(())
 }
@@ -422,6 +964,7 @@ async fn github_force_push(
     client: &GitClient,
     pr: &GitPr,
     repository: &Repo,
+    changelog_edits: Option<&ChangelogEdits>,
 ) -> anyhow::Result<()> {
     // Create a temporary branch.
     let tmp_release_branch = {
@@ -441,13 +984,30 @@ async fn github_force_push(
 
     repository.fetch(&tmp_release_branch.name)?;
 
+    let refspec = match changelog_edits {
+        Some(edits) => {
+            // Commit the edits on top of the "Verified" commit, so that the PR branch
+            // is rewritten with a single push.
+            repository.git(&[
+                "checkout",
+                "--force",
+                "-B",
+                &tmp_release_branch.name,
+                "FETCH_HEAD",
+            ])?;
+            commit_changelog_edits(repository, edits)
+                .context("cannot commit the changelog edits of the release PR")?;
+            format!("HEAD:{}", pr.branch())
+        }
+        None => format!(
+            "{}/{}:{}",
+            repository.original_remote(),
+            tmp_release_branch.name,
+            pr.branch()
+        ),
+    };
     // Rewrite the PR branch so that it's the same as the temporary branch.
-    repository.force_push(&format!(
-        "{}/{}:{}",
-        repository.original_remote(),
-        tmp_release_branch.name,
-        pr.branch()
-    ))?;
+    repository.force_push(&refspec)?;
 
     // The temporary branch is deleted in remote when it goes out of scope.
     Ok(())
@@ -504,3 +1064,233 @@ fn add_changes_and_commit(repository: &Repo, commit_message: &str) -> anyhow::Re
     repository.commit_signed(commit_message)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicting_branch_is_regenerated_from_default_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repo::init(tmp.path());
+        let readme = repo.directory().join("README.md");
+        repo.git(&["checkout", "-b", "release-plz-branch"]).unwrap();
+        fs_err::write(&readme, "release PR change").unwrap();
+        repo.add_all_and_commit("chore: release").unwrap();
+        repo.checkout(repo.original_branch()).unwrap();
+        fs_err::write(&readme, "conflicting change").unwrap();
+        repo.add_all_and_commit("docs: update readme").unwrap();
+        let default_branch_commit = repo.current_commit_hash().unwrap();
+
+        repo.checkout("release-plz-branch").unwrap();
+        rebase_on_original_branch(&repo).unwrap();
+        assert_eq!(repo.current_commit_hash().unwrap(), default_branch_commit);
+        assert!(repo.is_clean().is_ok());
+    }
+
+    #[test]
+    fn packages_are_grouped_by_version_group() {
+        let group = |version_group: Option<&str>, version_is_inherited, mode| {
+            group_name(
+                "my_package",
+                version_group.map(str::to_string),
+                version_is_inherited,
+                mode,
+            )
+        };
+        for mode in [ReleasePrMode::Package, ReleasePrMode::VersionGroup] {
+            assert_eq!(group(Some("core"), false, mode), "core");
+            assert_eq!(group(None, true, mode), WORKSPACE_GROUP);
+        }
+        assert_eq!(group(None, false, ReleasePrMode::Package), "my_package");
+        assert_eq!(
+            group(None, false, ReleasePrMode::VersionGroup),
+            UNGROUPED_GROUP
+        );
+    }
+
+    #[test]
+    fn pr_drafted_by_freeze_is_ready_when_freeze_ends() {
+        let freeze_banner = format!("> {FREEZE_DRAFT_NOTE} release freeze.\n\n## 🤖 New release");
+        let opened_pr = |draft: bool, body: &str| -> GitPr {
+            serde_json::from_value(serde_json::json!({
+                "user": { "login": "release-bot" },
+                "number": 1,
+                "html_url": "https://github.com/me/proj/pull/1",
+                "head": { "ref": "release-plz-2024-01-01T00-00-00Z", "sha": "abc" },
+                "title": "chore: release",
+                "body": body,
+                "labels": [],
+                "draft": draft,
+            }))
+            .unwrap()
+        };
+        let new_pr = |draft: bool| Pr {
+            base_branch: "main".to_string(),
+            branch: "release-plz-2024-01-01T00-00-00Z".to_string(),
+            title: "chore: release".to_string(),
+            body: "## 🤖 New release".to_string(),
+            draft,
+            labels: vec![],
+            reviewers: PrReviewers::default(),
+        };
+        assert!(is_freeze_draft_to_undo(
+            &opened_pr(true, &freeze_banner),
+            &new_pr(false),
+            false
+        ));
+        // The freeze is still active.
+        assert!(!is_freeze_draft_to_undo(
+            &opened_pr(true, &freeze_banner),
+            &new_pr(true),
+            true
+        ));
+        // The user asked for a draft PR with `pr_draft`.
+        assert!(!is_freeze_draft_to_undo(
+            &opened_pr(true, &freeze_banner),
+            &new_pr(true),
+            false
+        ));
+        // The user marked the PR as draft.
+        assert!(!is_freeze_draft_to_undo(
+            &opened_pr(true, "## 🤖 New release"),
+            &new_pr(false),
+            false
+        ));
+    }
+
+    /// Workspace with the packages `a`, `b` and `c`.
+    fn write_workspace(root: &Utf8Path) -> cargo_metadata::Metadata {
+        fs_err::write(
+            root.join(CARGO_TOML),
+            "[workspace]\nmembers = [\"a\", \"b\", \"c\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        for name in ["a", "b", "c"] {
+            fs_err::create_dir_all(root.join(name).join("src")).unwrap();
+            fs_err::write(root.join(name).join("src").join("lib.rs"), "").unwrap();
+            fs_err::write(
+                root.join(name).join(CARGO_TOML),
+                format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"),
+            )
+            .unwrap();
+        }
+        cargo_utils::get_manifest_metadata(&root.join(CARGO_TOML)).unwrap()
+    }
+
+    /// Update of the given packages to version `0.2.0`.
+    fn updates_of(metadata: &cargo_metadata::Metadata, packages: &[&str]) -> PackagesUpdate {
+        let updates = metadata
+            .workspace_packages()
+            .into_iter()
+            .filter(|package| packages.contains(&package.name.as_str()))
+            .map(|package| {
+                let update = crate::UpdateResult {
+                    version: Version::new(0, 2, 0),
+                    changelog: None,
+                    semver_check: crate::semver_check::SemverCheck::Skipped,
+                    package_contents: None,
+                };
+                (package.clone(), update)
+            })
+            .collect();
+        PackagesUpdate::new(updates)
+    }
+
+    #[test]
+    fn code_owners_of_updated_packages_are_reviewers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        let metadata = write_workspace(root);
+        fs_err::create_dir_all(root.join(".github")).unwrap();
+        fs_err::write(
+            root.join(".github").join("CODEOWNERS"),
+            "* @alice\n/a/ @bob @my-org/a-team\n/c/ @carol\n",
+        )
+        .unwrap();
+        let input = ReleasePrRequest::new(UpdateRequest::new(metadata.clone()).unwrap())
+            .with_reviewers(PrReviewers {
+                reviewers: vec!["dave".to_string()],
+                ..PrReviewers::default()
+            })
+            .with_reviewers_from_codeowners(true);
+
+        let reviewers = input
+            .pr_reviewers(&updates_of(&metadata, &["a", "b"]), root)
+            .unwrap();
+        assert_eq!(
+            reviewers,
+            PrReviewers {
+                reviewers: vec!["dave".to_string(), "bob".to_string(), "alice".to_string()],
+                team_reviewers: vec!["a-team".to_string()],
+                assignees: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn version_group_members_share_the_release_pr_in_package_mode() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        let metadata = write_workspace(root);
+        let in_core_group = crate::PackageUpdateConfig {
+            version_group: Some("core".to_string()),
+            ..Default::default()
+        };
+        let update_request = UpdateRequest::new(metadata.clone())
+            .unwrap()
+            .with_package_config("a", in_core_group.clone())
+            .with_package_config("b", in_core_group);
+        let input = ReleasePrRequest::new(update_request).with_mode(ReleasePrMode::Package);
+
+        let groups = release_pr_groups(&input, &updates_of(&metadata, &["a", "b", "c"])).unwrap();
+        assert_eq!(
+            groups,
+            BTreeMap::from([
+                ("c".to_string(), vec!["c".to_string()]),
+                ("core".to_string(), vec!["a".to_string(), "b".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn packages_without_version_group_share_the_release_pr_in_version_group_mode() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp.path()).unwrap();
+        let metadata = write_workspace(root);
+        let in_core_group = crate::PackageUpdateConfig {
+            version_group: Some("core".to_string()),
+            ..Default::default()
+        };
+        let update_request = UpdateRequest::new(metadata.clone())
+            .unwrap()
+            .with_package_config("a", in_core_group);
+        let updates = updates_of(&metadata, &["a", "b", "c"]);
+
+        let package_mode =
+            ReleasePrRequest::new(update_request.clone()).with_mode(ReleasePrMode::Package);
+        let package_groups = release_pr_groups(&package_mode, &updates).unwrap();
+        assert_eq!(
+            package_groups,
+            BTreeMap::from([
+                ("b".to_string(), vec!["b".to_string()]),
+                ("c".to_string(), vec!["c".to_string()]),
+                ("core".to_string(), vec!["a".to_string()]),
+            ])
+        );
+
+        let version_group_mode =
+            ReleasePrRequest::new(update_request).with_mode(ReleasePrMode::VersionGroup);
+        let version_groups = release_pr_groups(&version_group_mode, &updates).unwrap();
+        assert_eq!(
+            version_groups,
+            BTreeMap::from([
+                ("core".to_string(), vec!["a".to_string()]),
+                (
+                    UNGROUPED_GROUP.to_string(),
+                    vec!["b".to_string(), "c".to_string()]
+                ),
+            ])
+        );
+    }
+}
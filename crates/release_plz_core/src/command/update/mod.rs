@@ -5,7 +5,7 @@ mod update_config;
 pub mod update_request;
 pub mod updater;
 
-use crate::{PackagePath, tmp_repo::TempRepo};
+use crate::{PackageContentsDiff, PackagePath, tmp_repo::TempRepo};
 use crate::{fs_utils, root_repo_path_from_manifest_dir};
 use anyhow::Context;
 use cargo_metadata::camino::Utf8Path;
@@ -34,6 +34,8 @@ pub struct ReleaseInfo {
     /// Summary of breaking changes of the release
     breaking_changes: Option<String>,
     semver_check: String,
+    /// How the files of the package changed since the previous release
+    package_contents: Option<PackageContentsDiff>,
 }
 
 /// Update a local Rust project.
@@ -122,6 +122,7 @@ impl PackagesUpdate {
                     previous_version: package.version.to_string(),
                     breaking_changes,
                     semver_check: semver_check.to_string(),
+                    package_contents: update.package_contents.clone(),
                 }
             })
             .collect()
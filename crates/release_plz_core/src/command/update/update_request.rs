@@ -24,6 +24,8 @@ pub struct UpdateRequest {
     registry_manifest: Option<Utf8PathBuf>,
     /// Update just this package.
     single_package: Option<String>,
+    /// Update just these packages.
+    packages: Option<Vec<String>>,
     /// Changelog options.
     changelog_req: ChangelogRequest,
     /// Registry where the packages are stored.
@@ -45,6 +47,9 @@ pub struct UpdateRequest {
     /// Prepare release only if at least one commit respects a regex.
     release_commits: Option<Regex>,
     git: Option<GitForge>,
+    /// If true, compare the files of the updated packages with the files of the
+    /// packages published in the registry.
+    package_contents_diff: bool,
 }
 
 impl UpdateRequest {
@@ -56,6 +61,7 @@ impl UpdateRequest {
             metadata,
             registry_manifest: None,
             single_package: None,
+            packages: None,
             changelog_req: ChangelogRequest::default(),
             registry: None,
             dependencies_update: false,
@@ -64,6 +70,7 @@ impl UpdateRequest {
             packages_config: PackagesConfig::default(),
             release_commits: None,
             git: None,
+            package_contents_diff: false,
         })
     }
 
@@ -159,6 +166,13 @@ impl UpdateRequest {
         }
     }
 
+    pub fn with_packages(self, packages: Vec<String>) -> Self {
+        Self {
+            packages: Some(packages),
+            ..self
+        }
+    }
+
     pub fn with_repo_url(self, repo_url: RepoUrl) -> Self {
         Self {
             repo_url: Some(repo_url),
@@ -223,6 +237,10 @@ impl UpdateRequest {
         self.single_package.as_deref()
     }
 
+    pub fn packages(&self) -> Option<&[String]> {
+        self.packages.as_deref()
+    }
+
     pub fn changelog_req(&self) -> &ChangelogRequest {
         &self.changelog_req
     }
@@ -230,6 +248,17 @@ impl UpdateRequest {
     pub fn release_commits(&self) -> Option<&Regex> {
         self.release_commits.as_ref()
     }
+
+    pub fn with_package_contents_diff(self, package_contents_diff: bool) -> Self {
+        Self {
+            package_contents_diff,
+            ..self
+        }
+    }
+
+    pub fn should_diff_package_contents(&self) -> bool {
+        self.package_contents_diff
+    }
 }
 
 #[derive(Debug, Clone, Default)]
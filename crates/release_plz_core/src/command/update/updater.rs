@@ -26,6 +26,7 @@ use crate::{
     command::update::changelog_update::OldChangelogs,
     diff::{Commit, Diff},
     fs_utils, lock_compare,
+    package_contents::PackageContents,
     registry_packages::{PackagesCollection, RegistryPackage},
     semver_check::{self, SemverCheck},
     toml_compare,
@@ -133,9 +134,42 @@ impl Updater<'_> {
         let dependent_packages =
             self.dependent_packages_update(&packages_to_check_for_deps, &changed_packages)?;
         packages_to_update.updates_mut().extend(dependent_packages);
+        if self.req.should_diff_package_contents() {
+            self.fill_package_contents(&mut packages_to_update, registry_packages, repository)?;
+        }
         Ok(packages_to_update)
     }
 
+    /// Compare the files of the updated packages with the files of the packages
+    /// published in the registry.
+    fn fill_package_contents(
+        &self,
+        packages_to_update: &mut PackagesUpdate,
+        registry_packages: &PackagesCollection,
+        repository: &Repo,
+    ) -> anyhow::Result<()> {
+        // `cargo package --list` is slow, so we list the packages in parallel.
+        packages_to_update
+            .updates_mut()
+            .par_iter_mut()
+            .try_for_each(|(package, update)| {
+                let package_path = get_package_path(package, repository, self.project.root())
+                    .context("can't retrieve package path")?;
+                let contents = PackageContents::from_local_package(&package_path)?;
+                let registry_contents = registry_packages
+                    .get_package(&package.name)
+                    .map(|registry_package| {
+                        let registry_package_path = registry_package
+                            .package_path()
+                            .context("can't retrieve registry package path")?;
+                        PackageContents::from_registry_package(registry_package_path)
+                    })
+                    .transpose()?;
+                update.package_contents = Some(contents.diff(registry_contents.as_ref()));
+                anyhow::Ok(())
+            })
+    }
+
     /// Get the highest next version of all packages for each version group.
     fn get_version_groups(&self, packages_diffs: &[(&Package, Diff)]) -> HashMap<String, Version> {
         let mut version_groups: HashMap<String, Version> = HashMap::new();
@@ -472,6 +506,7 @@ impl Updater<'_> {
             version,
             changelog,
             semver_check,
+            package_contents: None,
         })
     }
 
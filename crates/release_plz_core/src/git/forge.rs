@@ -1,9 +1,10 @@
 use crate::git::{gitea_client::Gitea, gitlab_client::GitLab};
-use crate::{GitHub, GitReleaseInfo};
+use crate::{ExistingReleasePolicy, GitHub, GitReleaseInfo};
 use std::collections::{HashMap, HashSet};
 
 use crate::pr::Pr;
 use anyhow::Context;
+use cargo_metadata::camino::Utf8Path;
 use http::StatusCode;
 use itertools::Itertools;
 use reqwest::header::HeaderMap;
@@ -13,7 +14,7 @@ use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 #[derive(Debug, Clone)]
 pub enum GitForge {
@@ -44,6 +45,59 @@ pub struct GitClient {
     pub forge: ForgeType,
     pub remote: Remote,
     pub client: reqwest_middleware::ClientWithMiddleware,
+    /// Client without retries, for the requests with a streaming body,
+    /// e.g. multipart uploads, which the retry middleware can't clone.
+    upload_client: reqwest::Client,
+}
+
+/// File attached to a git release.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseAsset {
+    /// Name of the file.
+    pub name: String,
+    /// URL to download the file.
+    pub url: String,
+}
+
+/// Release returned by the GitHub and Gitea API.
+#[derive(Deserialize, Debug)]
+struct GitHubRelease {
+    id: u64,
+    #[serde(default)]
+    tag_name: String,
+    /// Only used for GitHub.
+    upload_url: Option<String>,
+    #[serde(default)]
+    assets: Vec<UploadedAsset>,
+}
+
+/// Release asset returned by the GitHub and Gitea API.
+#[derive(Deserialize, Debug, Clone)]
+struct UploadedAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl From<UploadedAsset> for ReleaseAsset {
+    fn from(asset: UploadedAsset) -> Self {
+        Self {
+            name: asset.name,
+            url: asset.browser_download_url,
+        }
+    }
+}
+
+/// Release returned by the GitLab API.
+#[derive(Deserialize, Debug)]
+struct GitlabRelease {
+    assets: GitlabReleaseAssets,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitlabReleaseAssets {
+    /// GitLab returns other fields, too. We only need `name` and `url`.
+    #[serde(default)]
+    links: Vec<ReleaseAsset>,
 }
 
 #[derive(Debug, Clone)]
@@ -107,6 +161,20 @@ pub struct GitPr {
     pub title: String,
     pub body: Option<String>,
     pub labels: Vec<Label>,
+    #[serde(default)]
+    pub draft: bool,
+    /// ID used by the GitHub GraphQL API.
+    #[serde(default)]
+    pub node_id: Option<String>,
+    /// Users requested to review the PR.
+    /// Gitea returns `null` instead of an empty array.
+    #[serde(default)]
+    pub requested_reviewers: Option<Vec<Author>>,
+    /// Teams requested to review the PR.
+    #[serde(default, alias = "requested_reviewers_teams")]
+    pub requested_teams: Option<Vec<Team>>,
+    #[serde(default)]
+    pub assignees: Option<Vec<Author>>,
 }
 
 /// Pull request.
@@ -118,6 +186,37 @@ impl GitPr {
     pub fn label_names(&self) -> Vec<&str> {
         self.labels.iter().map(|l| l.name.as_str()).collect()
     }
+
+    fn requested_reviewer_names(&self) -> Vec<&str> {
+        logins(self.requested_reviewers.as_deref())
+    }
+
+    fn requested_team_names(&self) -> Vec<&str> {
+        self.requested_teams
+            .iter()
+            .flatten()
+            .map(|team| team.slug.as_deref().unwrap_or(&team.name))
+            .collect()
+    }
+
+    fn assignee_names(&self) -> Vec<&str> {
+        logins(self.assignees.as_deref())
+    }
+}
+
+fn logins(authors: Option<&[Author]>) -> Vec<&str> {
+    authors
+        .unwrap_or_default()
+        .iter()
+        .map(|author| author.login.as_str())
+        .collect()
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Team {
+    /// Used by GitHub to identify the team. Not present in Gitea responses.
+    slug: Option<String>,
+    name: String,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -151,10 +250,13 @@ impl From<GitLabMr> for GitPr {
             },
             title: value.title,
             body,
-            user: Author {
-                login: value.author.username,
-            },
+            user: value.author.into(),
             labels,
+            draft: value.draft,
+            node_id: None,
+            requested_reviewers: Some(value.reviewers.into_iter().map(Author::from).collect()),
+            requested_teams: None,
+            assignees: Some(value.assignees.into_iter().map(Author::from).collect()),
         }
     }
 }
@@ -170,6 +272,12 @@ pub struct GitLabMr {
     pub title: String,
     pub description: String,
     pub labels: Vec<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub reviewers: Vec<GitLabAuthor>,
+    #[serde(default)]
+    pub assignees: Vec<GitLabAuthor>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -177,15 +285,29 @@ pub struct GitLabAuthor {
     pub username: String,
 }
 
+impl From<GitLabAuthor> for Author {
+    fn from(value: GitLabAuthor) -> Self {
+        Self {
+            login: value.username,
+        }
+    }
+}
+
+impl From<Author> for GitLabAuthor {
+    fn from(value: Author) -> Self {
+        Self {
+            username: value.login,
+        }
+    }
+}
+
 impl From<GitPr> for GitLabMr {
     fn from(value: GitPr) -> Self {
         let desc = value.body.unwrap_or_default();
         let labels: Vec<String> = value.labels.into_iter().map(|l| l.name).collect();
 
         GitLabMr {
-            author: GitLabAuthor {
-                username: value.user.login,
-            },
+            author: value.user.into(),
             iid: value.number,
             web_url: value.html_url,
             sha: value.head.sha,
@@ -193,6 +315,19 @@ impl From<GitPr> for GitLabMr {
             title: value.title,
             description: desc,
             labels,
+            draft: value.draft,
+            reviewers: value
+                .requested_reviewers
+                .unwrap_or_default()
+                .into_iter()
+                .map(GitLabAuthor::from)
+                .collect(),
+            assignees: value
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(GitLabAuthor::from)
+                .collect(),
         }
     }
 }
@@ -268,16 +403,15 @@ impl PrEdit {
 
 impl GitClient {
     pub fn new(forge: GitForge) -> anyhow::Result<Self> {
+        let headers = forge.default_headers()?;
+        let upload_client = reqwest::Client::builder()
+            .user_agent("release-plz")
+            .default_headers(headers)
+            .build()
+            .context("can't build Git client")?;
         let client = {
-            let headers = forge.default_headers()?;
-            let reqwest_client = reqwest::Client::builder()
-                .user_agent("release-plz")
-                .default_headers(headers)
-                .build()
-                .context("can't build Git client")?;
-
             let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
-            ClientBuilder::new(reqwest_client)
+            ClientBuilder::new(upload_client.clone())
                 // Retry failed requests.
                 .with(RetryTransientMiddleware::new_with_policy(retry_policy))
                 .build()
@@ -292,6 +426,7 @@ impl GitClient {
             remote,
             forge,
             client,
+            upload_client,
         })
     }
 
@@ -302,8 +437,11 @@ impl GitClient {
         }
     }
 
-    /// Creates a GitHub/Gitea release.
-    pub async fn create_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<()> {
+    /// Create the GitHub/Gitea/GitLab release of the tag, without the assets.
+    /// If the release already exists, handle it according to the [`ExistingReleasePolicy`]:
+    /// return `Ok(false)` if the release is skipped with [`ExistingReleasePolicy::Skip`].
+    /// Use [`Self::upload_release_assets`] to upload the assets.
+    pub async fn create_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<bool> {
         match self.forge {
             ForgeType::Github | ForgeType::Gitea => self.create_github_release(release_info).await,
             ForgeType::Gitlab => self.create_gitlab_release(release_info).await,
@@ -311,8 +449,26 @@ impl GitClient {
         .context("Failed to create release")
     }
 
+    /// Upload the assets that the release of the tag doesn't contain yet.
+    /// Return all the assets of `release_info`, including the ones that were already uploaded.
+    pub async fn upload_release_assets(
+        &self,
+        release_info: &GitReleaseInfo,
+    ) -> anyhow::Result<Vec<ReleaseAsset>> {
+        if release_info.assets.is_empty() {
+            return Ok(vec![]);
+        }
+        match self.forge {
+            ForgeType::Github | ForgeType::Gitea => {
+                self.upload_github_release_assets(release_info).await
+            }
+            ForgeType::Gitlab => self.upload_gitlab_release_assets(release_info).await,
+        }
+        .context("Failed to upload release assets")
+    }
+
     /// Same as Gitea.
-    pub async fn create_github_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<()> {
+    async fn create_github_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<bool> {
         if release_info.latest.is_some() && self.forge == ForgeType::Gitea {
             anyhow::bail!("Gitea does not support the `git_release_latest` option");
         }
@@ -324,32 +480,213 @@ impl GitClient {
             prerelease: &release_info.pre_release,
             make_latest: release_info.latest.map(|l| l.to_string()),
         };
-        self.client
-            .post(format!("{}/releases", self.repo_url()))
-            .json(&create_release_options)
+        let existing_release = self
+            .github_release(&release_info.git_tag)
+            .await
+            .context("can't check if the release already exists")?;
+        match existing_release {
+            None => {
+                self.client
+                    .post(format!("{}/releases", self.repo_url()))
+                    .json(&create_release_options)
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .map_err(|e| {
+                        if let Some(status) = e.status() {
+                            if status == reqwest::StatusCode::FORBIDDEN {
+                                return anyhow::anyhow!(e).context(
+                                    "Make sure your token has sufficient permissions. Learn more at https://release-plz.dev/docs/usage/release or https://release-plz.dev/docs/github/token",
+                                );
+                            }
+                        }
+                        anyhow::anyhow!(e)
+                    })?;
+            }
+            Some(existing_release) => {
+                if !should_update_release(release_info)? {
+                    return Ok(false);
+                }
+                self.client
+                    .patch(format!(
+                        "{}/releases/{}",
+                        self.repo_url(),
+                        existing_release.id
+                    ))
+                    .json(&create_release_options)
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await
+                    .context("can't update release")?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Same as Gitea.
+    async fn upload_github_release_assets(
+        &self,
+        release_info: &GitReleaseInfo,
+    ) -> anyhow::Result<Vec<ReleaseAsset>> {
+        let release = self
+            .github_release(&release_info.git_tag)
+            .await?
+            .with_context(|| format!("can't find the release of tag {}", release_info.git_tag))?;
+        let mut assets = vec![];
+        for path in &release_info.assets {
+            let name = asset_name(path)?;
+            if let Some(asset) = release.assets.iter().find(|a| a.name == name) {
+                info!("release asset {name} already uploaded");
+                assets.push(asset.clone().into());
+                continue;
+            }
+            let asset = if self.forge == ForgeType::Gitea {
+                self.upload_gitea_asset(&release, path).await
+            } else {
+                self.upload_github_asset(&release, path).await
+            }
+            .with_context(|| format!("failed to upload release asset {path:?}"))?;
+            assets.push(asset);
+        }
+        Ok(assets)
+    }
+
+    /// Get the GitHub or Gitea release of the given git tag.
+    async fn github_release(&self, tag: &str) -> anyhow::Result<Option<GitHubRelease>> {
+        if self.forge == ForgeType::Github {
+            self.github_release_by_tag(tag).await
+        } else {
+            self.release_by_tag(tag).await
+        }
+    }
+
+    /// Upload the asset to the `upload_url` returned by GitHub when creating the release.
+    async fn upload_github_asset(
+        &self,
+        release: &GitHubRelease,
+        path: &Utf8Path,
+    ) -> anyhow::Result<ReleaseAsset> {
+        let upload_url = release
+            .upload_url
+            .as_deref()
+            .context("GitHub didn't return the upload url of the release")?;
+        // The upload url is a hypermedia template, e.g. `https://uploads.github.com/repos/o/r/releases/1/assets{?name,label}`.
+        let upload_url = upload_url
+            .split_once('{')
+            .map_or(upload_url, |(url, _)| url);
+        let name = asset_name(path)?;
+        let content = fs_err::tokio::read(path).await?;
+        let uploaded: UploadedAsset = self
+            .client
+            .post(upload_url)
+            .query(&[("name", name)])
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(content)
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| {
-                if let Some(status) = e.status() {
-                    if status == reqwest::StatusCode::FORBIDDEN {
-                        return anyhow::anyhow!(e).context(
-                            "Make sure your token has sufficient permissions. Learn more at https://release-plz.dev/docs/usage/release or https://release-plz.dev/docs/github/token",
-                        );
-                    }
-                }
-                anyhow::anyhow!(e)
-            })?;
-        Ok(())
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("can't parse uploaded asset")?;
+        info!("uploaded release asset {name}");
+        Ok(ReleaseAsset {
+            name: name.to_string(),
+            url: uploaded.browser_download_url,
+        })
+    }
+
+    async fn upload_gitea_asset(
+        &self,
+        release: &GitHubRelease,
+        path: &Utf8Path,
+    ) -> anyhow::Result<ReleaseAsset> {
+        let name = asset_name(path)?;
+        let content = fs_err::tokio::read(path).await?;
+        let uploaded: UploadedAsset = self
+            .upload_client
+            .post(format!(
+                "{}/releases/{}/assets",
+                self.repo_url(),
+                release.id
+            ))
+            .query(&[("name", name)])
+            .multipart(multipart_file_form("attachment", name, content)?)
+            .send()
+            .await?
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("can't parse uploaded asset")?;
+        info!("uploaded release asset {name}");
+        Ok(ReleaseAsset {
+            name: name.to_string(),
+            url: uploaded.browser_download_url,
+        })
+    }
+
+    /// GitLab releases don't store files: the asset is uploaded to the project
+    /// and the release links to it.
+    async fn upload_gitlab_asset(&self, path: &Utf8Path) -> anyhow::Result<ReleaseAsset> {
+        #[derive(Deserialize)]
+        struct GitlabUpload {
+            full_path: String,
+        }
+        let name = asset_name(path)?;
+        let content = fs_err::tokio::read(path).await?;
+        let uploaded: GitlabUpload = self
+            .upload_client
+            .post(format!("{}/uploads", self.remote.base_url))
+            .multipart(multipart_file_form("file", name, content)?)
+            .send()
+            .await?
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("can't parse uploaded asset")?;
+        let url = self
+            .remote
+            .base_url
+            .join(&uploaded.full_path)
+            .context("invalid url of uploaded asset")?;
+        info!("uploaded release asset {name}");
+        Ok(ReleaseAsset {
+            name: name.to_string(),
+            url: url.to_string(),
+        })
     }
 
-    pub async fn create_gitlab_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<()> {
+    async fn create_gitlab_release(&self, release_info: &GitReleaseInfo) -> anyhow::Result<bool> {
         #[derive(Serialize)]
         pub struct GitlabReleaseOption<'a> {
             name: &'a str,
             tag_name: &'a str,
             description: &'a str,
         }
+        let existing_release: Option<GitlabRelease> = self
+            .release_by_tag(&release_info.git_tag)
+            .await
+            .context("can't check if the release already exists")?;
+        if existing_release.is_some() {
+            if !should_update_release(release_info)? {
+                return Ok(false);
+            }
+            self.client
+                .put(self.release_url(&release_info.git_tag)?)
+                .json(&json!({
+                    "name": release_info.release_name,
+                    "description": release_info.release_body,
+                }))
+                .send()
+                .await?
+                .successful_status()
+                .await
+                .context("can't update release")?;
+            return Ok(true);
+        }
         let gitlab_release_options = GitlabReleaseOption {
             name: &release_info.release_name,
             tag_name: &release_info.git_tag,
@@ -371,7 +708,101 @@ impl GitClient {
                 }
                 anyhow::anyhow!(e)
             })?;
-        Ok(())
+        Ok(true)
+    }
+
+    /// Upload the assets to the project and link them to the release.
+    async fn upload_gitlab_release_assets(
+        &self,
+        release_info: &GitReleaseInfo,
+    ) -> anyhow::Result<Vec<ReleaseAsset>> {
+        let release: GitlabRelease = self
+            .release_by_tag(&release_info.git_tag)
+            .await?
+            .with_context(|| format!("can't find the release of tag {}", release_info.git_tag))?;
+        let release_url = self.release_url(&release_info.git_tag)?;
+        let mut assets = vec![];
+        for path in &release_info.assets {
+            let name = asset_name(path)?;
+            if let Some(asset) = release.assets.links.iter().find(|a| a.name == name) {
+                info!("release asset {name} already uploaded");
+                assets.push(asset.clone());
+                continue;
+            }
+            let asset = self
+                .upload_gitlab_asset(path)
+                .await
+                .with_context(|| format!("failed to upload release asset {path:?}"))?;
+            self.client
+                .post(format!("{release_url}/assets/links"))
+                .json(&asset)
+                .send()
+                .await?
+                .successful_status()
+                .await
+                .with_context(|| format!("can't link release asset {}", asset.name))?;
+            assets.push(asset);
+        }
+        Ok(assets)
+    }
+
+    /// Get the release of the given git tag.
+    /// Return [`Option::None`] if the release doesn't exist.
+    async fn release_by_tag<T: serde::de::DeserializeOwned>(
+        &self,
+        tag: &str,
+    ) -> anyhow::Result<Option<T>> {
+        let resp = self.client.get(self.release_url(tag)?).send().await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let release = resp
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("can't parse release")?;
+        Ok(Some(release))
+    }
+
+    /// Get the GitHub release of the given git tag, including draft releases.
+    /// `GET /releases/tags/{tag}` doesn't return draft releases, so we search the list of releases.
+    /// Return [`Option::None`] if the release doesn't exist.
+    async fn github_release_by_tag(&self, tag: &str) -> anyhow::Result<Option<GitHubRelease>> {
+        const PER_PAGE: usize = 100;
+        for page in 1.. {
+            let releases: Vec<GitHubRelease> = self
+                .client
+                .get(format!("{}/releases", self.repo_url()))
+                .query(&[("per_page", PER_PAGE), ("page", page)])
+                .send()
+                .await?
+                .successful_status()
+                .await?
+                .json()
+                .await
+                .context("can't parse releases")?;
+            let releases_number = releases.len();
+            if let Some(release) = releases.into_iter().find(|r| r.tag_name == tag) {
+                return Ok(Some(release));
+            }
+            if releases_number < PER_PAGE {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    fn release_url(&self, tag: &str) -> anyhow::Result<Url> {
+        let releases_url = match self.forge {
+            ForgeType::Github | ForgeType::Gitea => format!("{}/releases/tags", self.repo_url()),
+            ForgeType::Gitlab => format!("{}/releases", self.repo_url()),
+        };
+        let mut url = Url::parse(&releases_url).context("invalid releases url")?;
+        url.path_segments_mut()
+            .map_err(|()| anyhow::anyhow!("invalid releases url {releases_url}"))?
+            .push(tag);
+        Ok(url)
     }
 
     pub fn pulls_url(&self) -> String {
@@ -485,6 +916,22 @@ impl GitClient {
         Ok(())
     }
 
+    /// Title that marks the PR as a draft.
+    /// GitLab and Gitea use a title prefix, while GitHub has a dedicated API
+    /// (see [`github_graphql::convert_pr_to_draft`](crate::git::github_graphql::convert_pr_to_draft)).
+    pub fn draft_title(&self, title: &str) -> String {
+        let prefix = match self.forge {
+            ForgeType::Github => return title.to_string(),
+            ForgeType::Gitea => "WIP: ",
+            ForgeType::Gitlab => "Draft: ",
+        };
+        if title.starts_with(prefix) {
+            title.to_string()
+        } else {
+            format!("{prefix}{title}")
+        }
+    }
+
     fn closed_pr_state(&self) -> &'static str {
         match self.forge {
             ForgeType::Github | ForgeType::Gitea => "closed",
@@ -564,6 +1011,9 @@ impl GitClient {
         self.add_labels(&pr.labels, git_pr.number)
             .await
             .context("Failed to add labels")?;
+        self.add_reviewers(&git_pr, &pr.reviewers)
+            .await
+            .context("Failed to add reviewers and assignees")?;
         Ok(git_pr)
     }
 
@@ -861,6 +1311,523 @@ impl GitClient {
         Ok(prs)
     }
 
+    /// Reviews of the PR. Only works for GitHub and Gitea.
+    async fn pr_reviews(&self, pr_number: u64) -> anyhow::Result<Vec<PrReview>> {
+        self.client
+            .get(format!("{}/{}/reviews", self.pulls_url(), pr_number))
+            .query(&[(self.per_page(), 100)])
+            .send()
+            .await?
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("failed to parse pr reviews")
+    }
+
+    /// Users whose latest review of the PR is an approval.
+    pub async fn pr_approvers(&self, pr_number: u64) -> anyhow::Result<Vec<String>> {
+        match self.forge {
+            ForgeType::Github | ForgeType::Gitea => {
+                let reviews = self.pr_reviews(pr_number).await?;
+                Ok(approvers(reviews))
+            }
+            ForgeType::Gitlab => {
+                let approvals: GitLabMrApprovals = self
+                    .client
+                    .get(format!(
+                        "{}/merge_requests/{pr_number}/approvals",
+                        self.repo_url()
+                    ))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?
+                    .json()
+                    .await
+                    .context("failed to parse gitlab mr approvals")?;
+                Ok(approvals
+                    .approved_by
+                    .into_iter()
+                    .map(|approver| approver.user.username)
+                    .collect())
+            }
+        }
+    }
+
+    /// Combined state of the statuses and checks of the commit.
+    pub async fn commit_status(&self, commit: &str) -> anyhow::Result<CommitStatus> {
+        let statuses = match self.forge {
+            ForgeType::Github | ForgeType::Gitea => {
+                let combined: CombinedStatus = self
+                    .client
+                    .get(format!("{}/commits/{commit}/status", self.repo_url()))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?
+                    .json()
+                    .await
+                    .context("failed to parse commit status")?;
+                let mut statuses = vec![];
+                // The combined state is `pending` if the commit has no statuses.
+                if combined.total_count > 0 {
+                    statuses.push(CommitStatus::from_state(&combined.state));
+                }
+                if self.forge == ForgeType::Github {
+                    // GitHub Actions reports check runs instead of statuses.
+                    let check_runs: GitHubCheckRuns = self
+                        .client
+                        .get(format!("{}/commits/{commit}/check-runs", self.repo_url()))
+                        .query(&[("per_page", 100)])
+                        .send()
+                        .await?
+                        .successful_status()
+                        .await?
+                        .json()
+                        .await
+                        .context("failed to parse check runs")?;
+                    statuses.extend(check_runs.check_runs.iter().map(|run| {
+                        match run.conclusion.as_deref() {
+                            Some(conclusion) if run.status == "completed" => {
+                                CommitStatus::from_state(conclusion)
+                            }
+                            _ => CommitStatus::Pending,
+                        }
+                    }));
+                }
+                statuses
+            }
+            ForgeType::Gitlab => {
+                let statuses: Vec<GitLabCommitStatus> = self
+                    .client
+                    .get(format!(
+                        "{}/repository/commits/{commit}/statuses",
+                        self.repo_url()
+                    ))
+                    .query(&[("per_page", 100)])
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?
+                    .json()
+                    .await
+                    .context("failed to parse gitlab commit statuses")?;
+                statuses
+                    .iter()
+                    .map(|status| CommitStatus::from_state(&status.status))
+                    .collect()
+            }
+        };
+        Ok(CommitStatus::combine(statuses))
+    }
+
+    fn milestones_url(&self) -> String {
+        format!("{}/milestones", self.repo_url())
+    }
+
+    /// Find the milestone with the given title, open or closed.
+    pub async fn find_milestone(&self, title: &str) -> anyhow::Result<Option<Milestone>> {
+        if self.forge == ForgeType::Gitlab {
+            // GitLab returns the milestones of all the states by default.
+            let milestones: Vec<Milestone> = self
+                .client
+                .get(self.milestones_url())
+                .query(&[("title", title)])
+                .send()
+                .await?
+                .successful_status()
+                .await?
+                .json()
+                .await
+                .context("failed to parse milestones")?;
+            return Ok(milestones.into_iter().find(|m| m.title == title));
+        }
+        let page_size = 50;
+        for page in 1.. {
+            let milestones: Vec<Milestone> = self
+                .client
+                .get(self.milestones_url())
+                .query(&[("state", "all")])
+                .query(&[(self.per_page(), page_size), ("page", page)])
+                .send()
+                .await?
+                .successful_status()
+                .await?
+                .json()
+                .await
+                .context("failed to parse milestones")?;
+            let is_last_page = milestones.len() < page_size;
+            if let Some(milestone) = milestones.into_iter().find(|m| m.title == title) {
+                return Ok(Some(milestone));
+            }
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn create_milestone(&self, title: &str) -> anyhow::Result<Milestone> {
+        let milestone = self
+            .client
+            .post(self.milestones_url())
+            .json(&json!({ "title": title }))
+            .send()
+            .await?
+            .successful_status()
+            .await?
+            .json()
+            .await
+            .context("failed to parse milestone")?;
+        info!("created milestone {title}");
+        Ok(milestone)
+    }
+
+    pub async fn close_milestone(&self, milestone: &Milestone) -> anyhow::Result<()> {
+        let url = format!("{}/{}", self.milestones_url(), milestone.api_id());
+        let req = match self.forge {
+            ForgeType::Github | ForgeType::Gitea => {
+                self.client.patch(url).json(&json!({ "state": "closed" }))
+            }
+            ForgeType::Gitlab => self
+                .client
+                .put(url)
+                .json(&json!({ "state_event": "close" })),
+        };
+        req.send().await?.successful_status().await?;
+        info!("closed milestone {}", milestone.title);
+        Ok(())
+    }
+
+    /// Assign the PR to the milestone.
+    pub async fn set_pr_milestone(
+        &self,
+        pr_number: u64,
+        milestone: &Milestone,
+    ) -> anyhow::Result<()> {
+        let req = match self.forge {
+            // GitHub and Gitea treat pull requests as issues.
+            ForgeType::Github | ForgeType::Gitea => self
+                .client
+                .patch(format!("{}/{pr_number}", self.issues_url()))
+                .json(&json!({ "milestone": milestone.api_id() })),
+            ForgeType::Gitlab => self
+                .client
+                .put(format!("{}/{pr_number}", self.pulls_url()))
+                .json(&json!({ "milestone_id": milestone.api_id() })),
+        };
+        req.send().await?.successful_status().await?;
+        Ok(())
+    }
+
+    /// Request the reviews and add the assignees of the PR.
+    /// Users and teams that were already requested and users that already reviewed
+    /// the PR are skipped, so that they aren't notified again when the PR is updated.
+    pub async fn add_reviewers(
+        &self,
+        pr: &GitPr,
+        reviewers: &crate::PrReviewers,
+    ) -> anyhow::Result<()> {
+        if reviewers.is_empty() {
+            return Ok(());
+        }
+        let reviewed_by = if reviewers.reviewers.is_empty() {
+            vec![]
+        } else {
+            self.pr_reviewed_by(pr.number).await?
+        };
+        let requested_reviewers = pr.requested_reviewer_names();
+        let new_reviewers: Vec<&str> = reviewers
+            .reviewers
+            .iter()
+            .map(String::as_str)
+            // GitHub doesn't allow the author of the PR to review it.
+            .filter(|user| {
+                *user != pr.user.login
+                    && !requested_reviewers.contains(user)
+                    && !reviewed_by.iter().any(|r| r == user)
+            })
+            .collect();
+        let requested_teams = pr.requested_team_names();
+        let new_teams: Vec<&str> = reviewers
+            .team_reviewers
+            .iter()
+            .map(String::as_str)
+            .filter(|team| !requested_teams.contains(team))
+            .collect();
+        if !new_reviewers.is_empty() || !new_teams.is_empty() {
+            self.request_reviewers(pr, &new_reviewers, &new_teams)
+                .await
+                .with_context(|| format!("cannot request reviewers for pr {}", pr.number))?;
+        }
+        let assignees = pr.assignee_names();
+        let new_assignees: Vec<&str> = reviewers
+            .assignees
+            .iter()
+            .map(String::as_str)
+            .filter(|user| !assignees.contains(user))
+            .collect();
+        if !new_assignees.is_empty() {
+            self.add_assignees(pr, &new_assignees)
+                .await
+                .with_context(|| format!("cannot add assignees to pr {}", pr.number))?;
+        }
+        Ok(())
+    }
+
+    async fn request_reviewers(
+        &self,
+        pr: &GitPr,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> anyhow::Result<()> {
+        let req = match self.forge {
+            ForgeType::Github | ForgeType::Gitea => self
+                .client
+                .post(format!(
+                    "{}/{}/requested_reviewers",
+                    self.pulls_url(),
+                    pr.number
+                ))
+                .json(&json!({
+                    "reviewers": reviewers,
+                    "team_reviewers": team_reviewers,
+                })),
+            ForgeType::Gitlab => {
+                if !team_reviewers.is_empty() {
+                    warn!("GitLab doesn't support team reviewers. Ignoring {team_reviewers:?}");
+                }
+                // The request replaces the reviewers, so we keep the existing ones.
+                let mut usernames = pr.requested_reviewer_names();
+                usernames.extend(reviewers);
+                let reviewer_ids = self.gitlab_user_ids(&usernames).await?;
+                self.client
+                    .put(format!("{}/{}", self.pulls_url(), pr.number))
+                    .json(&json!({ "reviewer_ids": reviewer_ids }))
+            }
+        };
+        req.send().await?.successful_status().await?;
+        info!(
+            "requested reviews of pr #{} to {reviewers:?} {team_reviewers:?}",
+            pr.number
+        );
+        Ok(())
+    }
+
+    async fn add_assignees(&self, pr: &GitPr, assignees: &[&str]) -> anyhow::Result<()> {
+        // Gitea and GitLab replace the assignees, so we keep the existing ones.
+        let mut all_assignees = pr.assignee_names();
+        all_assignees.extend(assignees);
+        let req = match self.forge {
+            // GitHub and Gitea treat pull requests as issues.
+            ForgeType::Github => self
+                .client
+                .post(format!("{}/{}/assignees", self.issues_url(), pr.number))
+                .json(&json!({ "assignees": assignees })),
+            ForgeType::Gitea => self
+                .client
+                .patch(format!("{}/{}", self.issues_url(), pr.number))
+                .json(&json!({ "assignees": all_assignees })),
+            ForgeType::Gitlab => {
+                let assignee_ids = self.gitlab_user_ids(&all_assignees).await?;
+                self.client
+                    .put(format!("{}/{}", self.pulls_url(), pr.number))
+                    .json(&json!({ "assignee_ids": assignee_ids }))
+            }
+        };
+        req.send().await?.successful_status().await?;
+        info!("assigned pr #{} to {assignees:?}", pr.number);
+        Ok(())
+    }
+
+    /// IDs of the GitLab users, which the GitLab API uses instead of the usernames.
+    async fn gitlab_user_ids(&self, usernames: &[&str]) -> anyhow::Result<Vec<u64>> {
+        #[derive(Deserialize)]
+        struct GitLabUser {
+            id: u64,
+        }
+
+        // The base url is the url of the project, e.g. `https://gitlab.com/api/v4/projects/owner%2Frepo`.
+        let users_url = self
+            .remote
+            .base_url
+            .join("../users")
+            .context("invalid GitLab users url")?;
+        let mut ids = vec![];
+        for username in usernames {
+            let users: Vec<GitLabUser> = self
+                .client
+                .get(users_url.clone())
+                .query(&[("username", username)])
+                .send()
+                .await?
+                .successful_status()
+                .await?
+                .json()
+                .await
+                .context("failed to parse gitlab users")?;
+            let user = users
+                .first()
+                .with_context(|| format!("GitLab user {username} not found"))?;
+            ids.push(user.id);
+        }
+        Ok(ids)
+    }
+
+    /// Users who submitted a review of the PR.
+    /// GitLab keeps the users in the reviewers after they review the merge request,
+    /// so this is only needed for GitHub and Gitea.
+    async fn pr_reviewed_by(&self, pr_number: u64) -> anyhow::Result<Vec<String>> {
+        match self.forge {
+            ForgeType::Github | ForgeType::Gitea => Ok(self
+                .pr_reviews(pr_number)
+                .await?
+                .into_iter()
+                .filter_map(|review| review.user.map(|user| user.login))
+                .unique()
+                .collect()),
+            ForgeType::Gitlab => Ok(vec![]),
+        }
+    }
+
+    /// Merge the PR automatically when the required checks pass.
+    pub async fn enable_auto_merge(
+        &self,
+        pr_number: u64,
+        merge_method: MergeMethod,
+    ) -> anyhow::Result<()> {
+        match self.forge {
+            ForgeType::Github => {
+                let pr = self.get_pr_info(pr_number).await?;
+                let node_id = pr
+                    .node_id
+                    .as_deref()
+                    .context("the GitHub PR doesn't have a node ID")?;
+                crate::git::github_graphql::enable_auto_merge(self, node_id, merge_method).await?;
+            }
+            ForgeType::Gitea => {
+                self.client
+                    .post(format!("{}/{pr_number}/merge", self.pulls_url()))
+                    .json(&json!({
+                        "Do": merge_method.as_str(),
+                        "merge_when_checks_succeed": true,
+                    }))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?;
+            }
+            ForgeType::Gitlab => {
+                // GitLab uses the merge method configured in the project,
+                // but the commits can be squashed for each merge request.
+                self.client
+                    .put(format!("{}/{pr_number}/merge", self.pulls_url()))
+                    .json(&json!({
+                        "merge_when_pipeline_succeeds": true,
+                        "squash": merge_method == MergeMethod::Squash,
+                    }))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?;
+            }
+        }
+        info!("enabled auto-merge for pr #{pr_number}");
+        Ok(())
+    }
+
+    fn comments_url(&self, item: IssueOrPr) -> String {
+        match (self.forge, item) {
+            // GitHub and Gitea treat pull requests as issues.
+            (ForgeType::Github | ForgeType::Gitea, item) => {
+                format!("{}/{}/comments", self.issues_url(), item.number())
+            }
+            (ForgeType::Gitlab, IssueOrPr::Pr(number)) => {
+                format!("{}/{number}/notes", self.pulls_url())
+            }
+            (ForgeType::Gitlab, IssueOrPr::Issue(number)) => {
+                format!("{}/{number}/notes", self.issues_url())
+            }
+        }
+    }
+
+    /// Bodies of the comments of the PR or issue.
+    pub async fn comments(&self, item: IssueOrPr) -> anyhow::Result<Vec<String>> {
+        // Gitea doesn't return more than 50 items per page by default.
+        let page_size = 50;
+        let mut bodies = vec![];
+        for page in 1.. {
+            let comments: Vec<IssueComment> = self
+                .client
+                .get(self.comments_url(item))
+                .query(&[(self.per_page(), page_size), ("page", page)])
+                .send()
+                .await?
+                .successful_status()
+                .await?
+                .json()
+                .await
+                .context("failed to parse comments")?;
+            let is_last_page = comments.len() < page_size;
+            bodies.extend(comments.into_iter().filter_map(|comment| comment.body));
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(bodies)
+    }
+
+    pub async fn add_comment(&self, item: IssueOrPr, body: &str) -> anyhow::Result<()> {
+        self.client
+            .post(self.comments_url(item))
+            .json(&json!({ "body": body }))
+            .send()
+            .await?
+            .successful_status()
+            .await?;
+        Ok(())
+    }
+
+    /// Add the label to the PR or issue.
+    /// If the label doesn't exist in the repository, it's created.
+    pub async fn add_label(&self, item: IssueOrPr, label: &str) -> anyhow::Result<()> {
+        let labels = [label.to_string()];
+        match (self.forge, item) {
+            (ForgeType::Github, item) => self.post_github_labels(&labels, item.number()).await,
+            (ForgeType::Gitlab, IssueOrPr::Pr(number)) => {
+                self.post_gitlab_labels(&labels, number).await
+            }
+            (ForgeType::Gitlab, IssueOrPr::Issue(number)) => {
+                self.client
+                    .put(format!("{}/{number}", self.issues_url()))
+                    .json(&json!({ "add_labels": label }))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?;
+                Ok(())
+            }
+            (ForgeType::Gitea, item) => {
+                let existing_labels = self.get_repository_labels().await?;
+                let label_id = match existing_labels.iter().find(|l| l.name == label) {
+                    Some(existing_label) => existing_label
+                        .id
+                        .with_context(|| format!("failed to extract id from label {label}"))?,
+                    None => self.create_gitea_repository_label(label).await?,
+                };
+                self.client
+                    .post(self.pr_labels_url(item.number()))
+                    .json(&json!({ "labels": [label_id] }))
+                    .send()
+                    .await?
+                    .successful_status()
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
     pub async fn get_remote_commit(&self, commit: &str) -> Result<RemoteCommit, anyhow::Error> {
         let api_path = self.commits_api_path(commit);
         let response = self.client.get(api_path).send().await?;
@@ -900,6 +1867,43 @@ impl GitClient {
     }
 }
 
+/// Apply the [`ExistingReleasePolicy`] to a release that already exists.
+/// Return `true` if the release should be updated.
+fn should_update_release(release_info: &GitReleaseInfo) -> anyhow::Result<bool> {
+    let tag = &release_info.git_tag;
+    match release_info.if_exists {
+        ExistingReleasePolicy::Fail => anyhow::bail!(
+            "release of tag {tag} already exists. Set `git_release_if_exists` to `update` or `skip` to handle existing releases"
+        ),
+        ExistingReleasePolicy::Skip => {
+            info!("release of tag {tag} already exists; skipping");
+            Ok(false)
+        }
+        ExistingReleasePolicy::Update => {
+            info!("release of tag {tag} already exists; updating it");
+            Ok(true)
+        }
+    }
+}
+
+/// Name of the asset in the git release.
+fn asset_name(path: &Utf8Path) -> anyhow::Result<&str> {
+    path.file_name()
+        .with_context(|| format!("invalid release asset path {path:?}"))
+}
+
+/// `multipart/form-data` form containing a single file.
+fn multipart_file_form(
+    field: &str,
+    file_name: &str,
+    content: Vec<u8>,
+) -> anyhow::Result<reqwest::multipart::Form> {
+    let part = reqwest::multipart::Part::bytes(content)
+        .file_name(file_name.to_string())
+        .mime_str("application/octet-stream")?;
+    Ok(reqwest::multipart::Form::new().part(field.to_string(), part))
+}
+
 pub fn validate_labels(labels: &[String]) -> anyhow::Result<()> {
     let mut unique_labels: HashSet<&str> = HashSet::new();
 
@@ -933,6 +1937,180 @@ pub fn validate_labels(labels: &[String]) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Review of a GitHub or Gitea pull request.
+#[derive(Deserialize, Debug)]
+struct PrReview {
+    user: Option<Author>,
+    state: String,
+    /// Only returned by Gitea. GitHub uses the `DISMISSED` state instead.
+    #[serde(default)]
+    dismissed: bool,
+}
+
+/// Users whose latest review is an approval.
+/// Comments don't change the outcome of the previous reviews of a user.
+fn approvers(reviews: Vec<PrReview>) -> Vec<String> {
+    let mut latest_reviews: Vec<(String, bool)> = vec![];
+    for review in reviews {
+        let Some(user) = review.user else {
+            continue;
+        };
+        let is_approved = match review.state.as_str() {
+            "APPROVED" => !review.dismissed,
+            "CHANGES_REQUESTED" | "REQUEST_CHANGES" | "DISMISSED" => false,
+            _ => continue,
+        };
+        latest_reviews.retain(|(login, _)| *login != user.login);
+        latest_reviews.push((user.login, is_approved));
+    }
+    latest_reviews
+        .into_iter()
+        .filter(|(_, is_approved)| *is_approved)
+        .map(|(login, _)| login)
+        .collect()
+}
+
+/// <https://docs.gitlab.com/api/merge_request_approvals/#retrieve-approval-state-for-a-merge-request>
+#[derive(Deserialize, Debug)]
+struct GitLabMrApprovals {
+    approved_by: Vec<GitLabApprover>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabApprover {
+    user: GitLabAuthor,
+}
+
+/// Combined status of a commit, returned by the GitHub and Gitea API.
+#[derive(Deserialize, Debug)]
+struct CombinedStatus {
+    state: String,
+    total_count: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubCheckRuns {
+    check_runs: Vec<GitHubCheckRun>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubCheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabCommitStatus {
+    status: String,
+}
+
+/// State of the statuses and checks of a commit, such as the CI jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitStatus {
+    Success,
+    Pending,
+    Failure,
+    /// The commit doesn't have any status.
+    Missing,
+}
+
+impl CommitStatus {
+    /// Parse the state of a status in the GitHub, Gitea or GitLab API.
+    fn from_state(state: &str) -> Self {
+        match state {
+            "success" | "neutral" | "skipped" | "warning" => Self::Success,
+            "pending"
+            | "queued"
+            | "in_progress"
+            | "created"
+            | "preparing"
+            | "running"
+            | "scheduled"
+            | "waiting_for_resource"
+            | "manual" => Self::Pending,
+            _ => Self::Failure,
+        }
+    }
+
+    /// The commit is successful only if all its statuses are successful.
+    fn combine(statuses: impl IntoIterator<Item = Self>) -> Self {
+        statuses
+            .into_iter()
+            .fold(Self::Missing, |combined, status| match (combined, status) {
+                (Self::Failure, _) | (_, Self::Failure) => Self::Failure,
+                (Self::Pending, _) | (_, Self::Pending) => Self::Pending,
+                _ => Self::Success,
+            })
+    }
+}
+
+/// Pull request or issue, identified by its number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueOrPr {
+    Pr(u64),
+    Issue(u64),
+}
+
+impl IssueOrPr {
+    pub fn number(self) -> u64 {
+        match self {
+            Self::Pr(number) | Self::Issue(number) => number,
+        }
+    }
+}
+
+/// How a PR is merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeMethod {
+    /// Add all the commits of the PR to the base branch with a merge commit.
+    Merge,
+    /// Combine the commits of the PR into one commit.
+    Squash,
+    /// Add all the commits of the PR to the base branch, without a merge commit.
+    Rebase,
+}
+
+impl MergeMethod {
+    /// Name of the merge method in the Gitea API.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Squash => "squash",
+            Self::Rebase => "rebase",
+        }
+    }
+}
+
+/// Milestone of GitHub, Gitea or GitLab.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Milestone {
+    /// ID used by the Gitea and GitLab API.
+    id: u64,
+    /// Number used by the GitHub API.
+    number: Option<u64>,
+    pub title: String,
+    /// `open`, `closed` (GitHub and Gitea), `active` or `closed` (GitLab).
+    pub state: String,
+}
+
+impl Milestone {
+    /// ID of the milestone in the API urls.
+    fn api_id(&self) -> u64 {
+        self.number.unwrap_or(self.id)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state == "closed"
+    }
+}
+
+/// Comment of a pull request or issue.
+/// GitLab calls them notes.
+#[derive(Deserialize, Debug)]
+struct IssueComment {
+    body: Option<String>,
+}
+
 /// Representation of a single commit.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitHubCommit {
@@ -992,7 +2170,298 @@ impl ResponseExt for reqwest::Response {
 
 #[cfg(test)]
 mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, method, path},
+    };
+
     use super::*;
+    use crate::RepoUrl;
+
+    /// GitHub client of a repository with a draft release of tag `v1.0.0`.
+    async fn github_with_draft_release(server: &MockServer) -> GitClient {
+        Mock::given(method("GET"))
+            .and(path("/repos/me/proj/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": 2, "tag_name": "v1.1.0", "draft": false, "assets": [] },
+                { "id": 1, "tag_name": "v1.0.0", "draft": true, "assets": [] },
+            ])))
+            .mount(server)
+            .await;
+        // The releases of a tag don't include drafts.
+        Mock::given(method("GET"))
+            .and(path("/repos/me/proj/releases/tags/v1.0.0"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/me/proj/releases"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(server)
+            .await;
+        let github = GitHub::new("me".to_string(), "proj".to_string(), "token".into())
+            .with_base_url(server.uri().parse().unwrap());
+        GitClient::new(GitForge::Github(github)).unwrap()
+    }
+
+    fn release_info(if_exists: ExistingReleasePolicy) -> GitReleaseInfo {
+        GitReleaseInfo {
+            git_tag: "v1.0.0".to_string(),
+            release_name: "v1.0.0".to_string(),
+            release_body: "changes".to_string(),
+            latest: None,
+            draft: false,
+            pre_release: false,
+            assets: vec![],
+            if_exists,
+        }
+    }
+
+    #[tokio::test]
+    async fn existing_github_draft_release_fails_release() {
+        let server = MockServer::start().await;
+        let client = github_with_draft_release(&server).await;
+        let error = client
+            .create_release(&release_info(ExistingReleasePolicy::Fail))
+            .await
+            .unwrap_err();
+        assert!(format!("{error:?}").contains("release of tag v1.0.0 already exists"));
+    }
+
+    #[tokio::test]
+    async fn existing_github_draft_release_is_skipped() {
+        let server = MockServer::start().await;
+        let client = github_with_draft_release(&server).await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let is_created = client
+            .create_release(&release_info(ExistingReleasePolicy::Skip))
+            .await
+            .unwrap();
+        assert!(!is_created);
+    }
+
+    #[tokio::test]
+    async fn existing_github_draft_release_is_updated() {
+        let server = MockServer::start().await;
+        let client = github_with_draft_release(&server).await;
+        Mock::given(method("PATCH"))
+            .and(path("/repos/me/proj/releases/1"))
+            .and(body_json(json!({
+                "tag_name": "v1.0.0",
+                "body": "changes",
+                "name": "v1.0.0",
+                "draft": false,
+                "prerelease": false,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": 1, "tag_name": "v1.0.0", "draft": false, "assets": [],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        client
+            .create_release(&release_info(ExistingReleasePolicy::Update))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn gitea_auto_merge_is_scheduled() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/me/proj/pulls/3/merge"))
+            .and(body_json(json!({
+                "Do": "squash",
+                "merge_when_checks_succeed": true,
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = RepoUrl::new(&format!("{}/me/proj", server.uri())).unwrap();
+        let gitea = Gitea::new(url, "token".into()).unwrap();
+        let client = GitClient::new(GitForge::Gitea(gitea)).unwrap();
+        client
+            .enable_auto_merge(3, MergeMethod::Squash)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_new_reviewers_and_assignees_are_added() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/me/proj/pulls/3/reviews"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "user": { "login": "bob" }, "state": "COMMENT" },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/me/proj/pulls/3/requested_reviewers"))
+            .and(body_json(json!({
+                "reviewers": ["dave"],
+                "team_reviewers": [],
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/api/v1/repos/me/proj/issues/3"))
+            .and(body_json(json!({ "assignees": ["carol", "erin"] })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = RepoUrl::new(&format!("{}/me/proj", server.uri())).unwrap();
+        let gitea = Gitea::new(url, "token".into()).unwrap();
+        let client = GitClient::new(GitForge::Gitea(gitea)).unwrap();
+        let pr: GitPr = serde_json::from_value(json!({
+            "user": { "login": "release-bot" },
+            "number": 3,
+            "html_url": format!("{}/me/proj/pulls/3", server.uri()),
+            "head": { "ref": "release-plz-2024-01-01T00-00-00Z", "sha": "abc" },
+            "title": "chore: release",
+            "body": null,
+            "labels": [],
+            "requested_reviewers": [{ "login": "alice" }],
+            "assignees": [{ "login": "carol" }],
+        }))
+        .unwrap();
+        let reviewers = crate::PrReviewers {
+            // `release-bot` authored the PR, `alice` was already requested and `bob` already reviewed.
+            reviewers: ["alice", "bob", "dave", "release-bot"]
+                .map(String::from)
+                .to_vec(),
+            team_reviewers: vec![],
+            assignees: ["carol", "erin"].map(String::from).to_vec(),
+        };
+        client.add_reviewers(&pr, &reviewers).await.unwrap();
+    }
+
+    #[test]
+    fn release_url_contains_encoded_tag() {
+        let github = GitHub::new("me".to_string(), "proj".to_string(), "token".into());
+        let client = GitClient::new(GitForge::Github(github)).unwrap();
+        assert_eq!(
+            client.release_url("my/pkg-v1.0.0").unwrap().as_str(),
+            "https://api.github.com/repos/me/proj/releases/tags/my%2Fpkg-v1.0.0"
+        );
+
+        let repo_url = crate::RepoUrl::new("https://gitlab.com/me/proj").unwrap();
+        let gitlab = GitLab::new(repo_url, "token".into()).unwrap();
+        let client = GitClient::new(GitForge::Gitlab(gitlab)).unwrap();
+        assert_eq!(
+            client.release_url("v1.0.0").unwrap().as_str(),
+            "https://gitlab.com/api/v4/projects/me%2Fproj/releases/v1.0.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn only_missing_gitea_assets_are_uploaded() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/me/proj/releases/tags/v1.0.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": 1,
+                "tag_name": "v1.0.0",
+                "assets": [{ "name": "uploaded.txt", "browser_download_url": "https://example.com/uploaded.txt" }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/me/proj/releases/1/assets"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "my\"file.txt",
+                "browser_download_url": "https://example.com/my-file.txt",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        let assets = vec![dir.join("uploaded.txt"), dir.join("my\"file.txt")];
+        for asset in &assets {
+            fs_err::write(asset, "hello").unwrap();
+        }
+        let url = RepoUrl::new(&format!("{}/me/proj", server.uri())).unwrap();
+        let gitea = Gitea::new(url, "token".into()).unwrap();
+        let client = GitClient::new(GitForge::Gitea(gitea)).unwrap();
+
+        let uploaded = client
+            .upload_release_assets(&GitReleaseInfo {
+                assets,
+                ..release_info(ExistingReleasePolicy::Fail)
+            })
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = uploaded.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["uploaded.txt", "my\"file.txt"]);
+        let requests = server.received_requests().await.unwrap();
+        let upload = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body = String::from_utf8_lossy(&upload.body);
+        // The quote of the file name doesn't break the `Content-Disposition` header.
+        assert!(
+            body.contains(r#"name="attachment"; filename="my\"file.txt""#),
+            "{body}"
+        );
+    }
+
+    #[test]
+    fn latest_review_of_each_user_counts() {
+        let reviews: Vec<PrReview> = serde_json::from_value(serde_json::json!([
+            { "user": { "login": "alice" }, "state": "APPROVED" },
+            { "user": { "login": "alice" }, "state": "COMMENTED" },
+            { "user": { "login": "bob" }, "state": "APPROVED" },
+            { "user": { "login": "bob" }, "state": "CHANGES_REQUESTED" },
+            { "user": { "login": "carol" }, "state": "APPROVED", "dismissed": true },
+            { "user": { "login": "dave" }, "state": "REQUEST_CHANGES" },
+            { "user": { "login": "dave" }, "state": "APPROVED" },
+        ]))
+        .unwrap();
+        assert_eq!(approvers(reviews), vec!["alice", "dave"]);
+    }
+
+    #[test]
+    fn commit_status_is_combined() {
+        use CommitStatus::{Failure, Missing, Pending, Success};
+        assert_eq!(CommitStatus::combine([]), Missing);
+        assert_eq!(CommitStatus::combine([Success, Success]), Success);
+        assert_eq!(CommitStatus::combine([Success, Pending]), Pending);
+        assert_eq!(CommitStatus::combine([Pending, Failure, Success]), Failure);
+        assert_eq!(CommitStatus::from_state("failed"), Failure);
+        assert_eq!(CommitStatus::from_state("skipped"), Success);
+    }
+
+    #[test]
+    fn milestone_api_id_depends_on_forge() {
+        let github_milestone: Milestone = serde_json::from_value(serde_json::json!({
+            "id": 1_002_604,
+            "number": 1,
+            "title": "v1.0",
+            "state": "open",
+        }))
+        .unwrap();
+        assert_eq!(github_milestone.api_id(), 1);
+        let gitlab_milestone: Milestone = serde_json::from_value(serde_json::json!({
+            "id": 12,
+            "iid": 3,
+            "title": "v1.0",
+            "state": "closed",
+        }))
+        .unwrap();
+        assert_eq!(gitlab_milestone.api_id(), 12);
+        assert!(gitlab_milestone.is_closed());
+    }
 
     #[test]
     fn contributors_are_extracted_from_commits() {
@@ -7,7 +7,7 @@ use tracing::{debug, trace};
 use url::Url;
 
 use crate::GitClient;
-use crate::git::forge::Remote;
+use crate::git::forge::{MergeMethod, Remote};
 
 /// Commit all the changes (except typestates) that are present in the repository
 /// using GitHub's [GraphQL api](https://docs.github.com/en/graphql/reference/mutations#createcommitonbranch).
@@ -47,6 +47,107 @@ pub async fn commit_changes(
     Ok(())
 }
 
+/// Mark an open PR as a draft with GitHub's
+/// [GraphQL api](https://docs.github.com/en/graphql/reference/mutations#convertpullrequesttodraft).
+/// The REST API doesn't support it.
+pub async fn convert_pr_to_draft(client: &GitClient, pr_node_id: &str) -> Result<()> {
+    let graphql_endpoint = get_graphql_endpoint(&client.remote);
+    let query = json!({
+        "query": "mutation($id: ID!) { convertPullRequestToDraft(input: {pullRequestId: $id}) { clientMutationId } }",
+        "variables": { "id": pr_node_id },
+    });
+    debug!("Sending convertPullRequestToDraft to {}", graphql_endpoint);
+
+    let res: Value = client
+        .client
+        .post(graphql_endpoint)
+        .json(&query)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(errors) = res.get("errors").and_then(Value::as_array) {
+        anyhow::bail!(
+            "convertPullRequestToDraft returned errors: {:?}",
+            serde_json::to_string(errors)?
+        );
+    }
+
+    Ok(())
+}
+
+/// Mark a draft PR as ready for review with GitHub's
+/// [GraphQL api](https://docs.github.com/en/graphql/reference/mutations#markpullrequestreadyforreview).
+/// The REST API doesn't support it.
+pub async fn mark_pr_ready_for_review(client: &GitClient, pr_node_id: &str) -> Result<()> {
+    let graphql_endpoint = get_graphql_endpoint(&client.remote);
+    let query = json!({
+        "query": "mutation($id: ID!) { markPullRequestReadyForReview(input: {pullRequestId: $id}) { clientMutationId } }",
+        "variables": { "id": pr_node_id },
+    });
+    debug!(
+        "Sending markPullRequestReadyForReview to {}",
+        graphql_endpoint
+    );
+
+    let res: Value = client
+        .client
+        .post(graphql_endpoint)
+        .json(&query)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(errors) = res.get("errors").and_then(Value::as_array) {
+        anyhow::bail!(
+            "markPullRequestReadyForReview returned errors: {:?}",
+            serde_json::to_string(errors)?
+        );
+    }
+
+    Ok(())
+}
+
+/// Merge the PR automatically when the required checks pass, using GitHub's
+/// [GraphQL api](https://docs.github.com/en/graphql/reference/mutations#enablepullrequestautomerge).
+pub async fn enable_auto_merge(
+    client: &GitClient,
+    pr_node_id: &str,
+    merge_method: MergeMethod,
+) -> Result<()> {
+    let graphql_endpoint = get_graphql_endpoint(&client.remote);
+    let merge_method = match merge_method {
+        MergeMethod::Merge => "MERGE",
+        MergeMethod::Squash => "SQUASH",
+        MergeMethod::Rebase => "REBASE",
+    };
+    let query = json!({
+        "query": "mutation($id: ID!, $method: PullRequestMergeMethod!) { enablePullRequestAutoMerge(input: {pullRequestId: $id, mergeMethod: $method}) { clientMutationId } }",
+        "variables": { "id": pr_node_id, "method": merge_method },
+    });
+    debug!("Sending enablePullRequestAutoMerge to {}", graphql_endpoint);
+
+    let res: Value = client
+        .client
+        .post(graphql_endpoint)
+        .json(&query)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(errors) = res.get("errors").and_then(Value::as_array) {
+        anyhow::bail!(
+            "enablePullRequestAutoMerge returned errors: {:?}",
+            serde_json::to_string(errors)?
+        );
+    }
+
+    Ok(())
+}
+
 fn get_graphql_endpoint(remote: &Remote) -> Url {
     let mut base_url = remote.base_url.clone();
     base_url.set_path("graphql");
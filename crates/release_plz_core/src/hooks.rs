@@ -0,0 +1,254 @@
+use anyhow::Context as _;
+use cargo_metadata::camino::Utf8Path;
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+/// Point of the release of a package where a hook runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    /// Before `cargo publish`.
+    PrePublish,
+    /// After `cargo publish`.
+    PostPublish,
+    /// After the git tag is pushed.
+    PostTag,
+    /// After the git release is created.
+    PostRelease,
+}
+
+impl HookKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PrePublish => "pre_publish",
+            Self::PostPublish => "post_publish",
+            Self::PostTag => "post_tag",
+            Self::PostRelease => "post_release",
+        }
+    }
+}
+
+/// Commands that release-plz runs while releasing a package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    pre_publish: Vec<String>,
+    post_publish: Vec<String>,
+    post_tag: Vec<String>,
+    post_release: Vec<String>,
+}
+
+impl Hooks {
+    pub fn with_pre_publish(mut self, commands: Vec<String>) -> Self {
+        self.pre_publish = commands;
+        self
+    }
+
+    pub fn with_post_publish(mut self, commands: Vec<String>) -> Self {
+        self.post_publish = commands;
+        self
+    }
+
+    pub fn with_post_tag(mut self, commands: Vec<String>) -> Self {
+        self.post_tag = commands;
+        self
+    }
+
+    pub fn with_post_release(mut self, commands: Vec<String>) -> Self {
+        self.post_release = commands;
+        self
+    }
+
+    pub fn commands(&self, kind: HookKind) -> &[String] {
+        match kind {
+            HookKind::PrePublish => &self.pre_publish,
+            HookKind::PostPublish => &self.post_publish,
+            HookKind::PostTag => &self.post_tag,
+            HookKind::PostRelease => &self.post_release,
+        }
+    }
+}
+
+/// Information about the release exposed to the hooks as environment variables.
+#[derive(Debug)]
+pub struct HookContext<'a> {
+    pub package: &'a str,
+    pub version: &'a str,
+    pub tag: &'a str,
+    /// [`Option::None`] means crates.io.
+    pub registry: Option<&'a str>,
+    pub changelog: &'a str,
+}
+
+impl HookContext<'_> {
+    fn env_vars(&self, kind: HookKind) -> [(&'static str, &str); 6] {
+        [
+            ("RELEASE_PLZ_HOOK", kind.name()),
+            ("RELEASE_PLZ_PACKAGE", self.package),
+            ("RELEASE_PLZ_VERSION", self.version),
+            ("RELEASE_PLZ_TAG", self.tag),
+            ("RELEASE_PLZ_REGISTRY", self.registry.unwrap_or("crates-io")),
+            ("RELEASE_PLZ_CHANGELOG", self.changelog),
+        ]
+    }
+}
+
+/// Hook that returned an error.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FailedHook {
+    /// Kind of hook, e.g. `post_tag`.
+    pub hook: String,
+    pub command: String,
+    pub error: String,
+}
+
+/// Run the commands of the hook in order, from the workspace root.
+/// Stop at the first command that fails.
+pub async fn run_hook(
+    hooks: &Hooks,
+    kind: HookKind,
+    context: &HookContext<'_>,
+    workspace_root: &Utf8Path,
+) -> Result<(), FailedHook> {
+    for command in hooks.commands(kind) {
+        info!(
+            "{} {}: running {} hook `{command}`",
+            context.package,
+            context.version,
+            kind.name()
+        );
+        run_command(command, kind, context, workspace_root)
+            .await
+            .map_err(|e| FailedHook {
+                hook: kind.name().to_string(),
+                command: command.clone(),
+                error: format!("{e:?}"),
+            })?;
+    }
+    Ok(())
+}
+
+/// Run the hook and return an error if it fails.
+/// Use it for hooks that must stop the release, like `pre_publish`.
+pub async fn run_required_hook(
+    hooks: &Hooks,
+    kind: HookKind,
+    context: &HookContext<'_>,
+    workspace_root: &Utf8Path,
+) -> anyhow::Result<()> {
+    run_hook(hooks, kind, context, workspace_root)
+        .await
+        .map_err(|failure| {
+            anyhow::anyhow!(
+                "{} hook `{}` failed: {}",
+                failure.hook,
+                failure.command,
+                failure.error
+            )
+        })
+}
+
+/// Run the hook and collect its failure, without stopping the release.
+/// Use it for hooks that run after a step that can't be undone, like `post_publish`.
+pub async fn run_optional_hook(
+    hooks: &Hooks,
+    kind: HookKind,
+    context: &HookContext<'_>,
+    workspace_root: &Utf8Path,
+    failed_hooks: &mut Vec<FailedHook>,
+) {
+    if let Err(failure) = run_hook(hooks, kind, context, workspace_root).await {
+        warn!(
+            "{} {}: {} hook `{}` failed: {}",
+            context.package, context.version, failure.hook, failure.command, failure.error
+        );
+        failed_hooks.push(failure);
+    }
+}
+
+async fn run_command(
+    command: &str,
+    kind: HookKind,
+    context: &HookContext<'_>,
+    workspace_root: &Utf8Path,
+) -> anyhow::Result<()> {
+    let output = shell_command(command)
+        .current_dir(workspace_root)
+        .envs(context.env_vars(kind))
+        .output()
+        .await
+        .context("can't run hook")?;
+    // Don't print the output of the hook to stdout,
+    // because stdout is reserved to the output of release-plz.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    debug!("hook stdout: {stdout}");
+    debug!("hook stderr: {stderr}");
+    anyhow::ensure!(
+        output.status.success(),
+        "hook exited with {}. Stderr: {stderr}",
+        output.status
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs_utils::Utf8TempDir;
+
+    use super::*;
+
+    fn context() -> HookContext<'static> {
+        HookContext {
+            package: "my_package",
+            version: "0.1.0",
+            tag: "v0.1.0",
+            registry: None,
+            changelog: "my changes",
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hook_receives_release_info() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let hooks = Hooks::default().with_post_tag(vec![
+            r#"echo "$RELEASE_PLZ_HOOK $RELEASE_PLZ_PACKAGE $RELEASE_PLZ_TAG $RELEASE_PLZ_REGISTRY" > hook.txt"#
+                .to_string(),
+        ]);
+        run_hook(&hooks, HookKind::PostTag, &context(), temp_dir.path())
+            .await
+            .unwrap();
+        let hook_output = fs_err::read_to_string(temp_dir.path().join("hook.txt")).unwrap();
+        assert_eq!(hook_output, "post_tag my_package v0.1.0 crates-io\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_hook_stops_next_commands() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let hooks = Hooks::default().with_pre_publish(vec![
+            "echo oops >&2; exit 3".to_string(),
+            "touch ran".to_string(),
+        ]);
+        let failure = run_hook(&hooks, HookKind::PrePublish, &context(), temp_dir.path())
+            .await
+            .unwrap_err();
+        assert_eq!(failure.hook, "pre_publish");
+        assert_eq!(failure.command, "echo oops >&2; exit 3");
+        assert!(failure.error.contains("oops"));
+        assert!(!temp_dir.path().join("ran").exists());
+    }
+}
@@ -5,21 +5,36 @@ mod changelog;
 mod changelog_filler;
 mod changelog_parser;
 mod clone;
+mod codeowners;
 mod command;
 mod copy_dir;
 mod diff;
 mod download;
 pub mod fs_utils;
 mod git;
+mod hooks;
+mod local_registry;
 mod lock_compare;
+mod metadata_lint;
+mod milestone;
 mod next_ver;
+mod notification;
 mod package_compare;
+mod package_contents;
 mod package_path;
 mod pr;
 mod pr_parser;
 mod project;
+mod publish_error;
+mod publish_verification;
+mod redact;
+mod registry_config;
 mod registry_packages;
+mod release_freeze;
+mod release_gates;
+mod release_journal;
 mod release_order;
+mod released_prs;
 mod repo_url;
 pub mod semver_check;
 mod tera;
@@ -30,13 +45,23 @@ mod version;
 pub use changelog::*;
 pub use command::*;
 pub use download::{PackageDownloader, read_package};
-pub use git::forge::{GitClient, GitForge, GitPr};
+pub use git::forge::{GitClient, GitForge, GitPr, MergeMethod, ReleaseAsset};
 pub use git::gitea_client::Gitea;
 pub use git::github_client::GitHub;
 pub use git::gitlab_client::GitLab;
+pub use hooks::Hooks;
+pub use local_registry::LocalRegistry;
 pub use next_ver::*;
+pub use notification::{Notification, NotificationEvent, send_notifications};
 pub use package_compare::*;
+pub use package_contents::{PackageContentsDiff, PackageContentsLimits};
 pub use package_path::*;
-pub use pr::{DEFAULT_BRANCH_PREFIX, Pr};
+pub use pr::{DEFAULT_BRANCH_PREFIX, Pr, PrReviewers};
 pub use project::*;
+pub use publish_error::{PublishError, PublishErrorKind};
+pub use redact::{REDACTED, add_secret, add_secret_string, redact_secrets};
+pub use registry_config::{CRATES_IO_REGISTRY_NAME, RegistryConfig, RegistryTokenSource};
+pub use release_freeze::{ActiveFreeze, FreezeWindow};
+pub use release_gates::ReleaseGates;
+pub use released_prs::ReleasedPrsConfig;
 pub use repo_url::*;
@@ -0,0 +1,255 @@
+//! Cargo registry stored in a local directory.
+//!
+//! It's used to rehearse a release without uploading anything to a remote registry:
+//! release-plz packages the crates with `cargo package`, copies the `.crate` files
+//! into the directory and adds them to an index with the sparse index layout.
+
+use std::{collections::BTreeMap, io::Write as _};
+
+use anyhow::Context as _;
+use cargo_metadata::{
+    DependencyKind, Package,
+    camino::{Utf8Path, Utf8PathBuf},
+    semver::VersionReq,
+};
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+use tracing::debug;
+
+/// Index of crates.io. Used for the dependencies that aren't in the local registry.
+const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// Directory where release-plz "publishes" packages.
+///
+/// The directory contains:
+/// - `config.json`: the [index configuration](https://doc.rust-lang.org/cargo/reference/registry-index.html#index-configuration).
+/// - `index/`: one file per crate, with the sparse index layout.
+/// - `crates/`: the `.crate` files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalRegistry {
+    path: Utf8PathBuf,
+}
+
+/// Entry of the index file of a crate.
+/// See the [index format](https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema).
+#[derive(Serialize, Debug)]
+struct IndexEntry<'a> {
+    name: &'a str,
+    vers: String,
+    deps: Vec<IndexDependency<'a>>,
+    cksum: String,
+    features: BTreeMap<&'a str, &'a [String]>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    features2: BTreeMap<&'a str, &'a [String]>,
+    yanked: bool,
+    links: Option<&'a str>,
+    v: u32,
+}
+
+#[derive(Serialize, Debug)]
+struct IndexDependency<'a> {
+    name: &'a str,
+    req: String,
+    features: &'a [String],
+    optional: bool,
+    default_features: bool,
+    target: Option<String>,
+    kind: DependencyKind,
+    registry: Option<&'a str>,
+    package: Option<&'a str>,
+}
+
+impl LocalRegistry {
+    pub fn new(path: impl Into<Utf8PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Check if the version of the package is in the index.
+    pub fn is_published(&self, package: &Package) -> anyhow::Result<bool> {
+        let index_file = self.index_file(&package.name);
+        if !index_file.exists() {
+            return Ok(false);
+        }
+        let crate_data = crates_index::Crate::new(&index_file)
+            .with_context(|| format!("can't parse index file {index_file:?}"))?;
+        let version = package.version.to_string();
+        Ok(crate_data.versions().iter().any(|v| v.version() == version))
+    }
+
+    /// Copy the `.crate` file of the package into the registry and add it to the index.
+    pub fn publish(&self, package: &Package, crate_file: &Utf8Path) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.is_published(package)?,
+            "crate {}@{} already exists in the local registry {:?}",
+            package.name,
+            package.version,
+            self.path
+        );
+        self.write_config()?;
+
+        let crate_content = fs_err::read(crate_file)?;
+        let destination = self.crate_file(package);
+        if let Some(parent) = destination.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(&destination, &crate_content)?;
+
+        let entry = index_entry(package, &crate_content);
+        let entry = serde_json::to_string(&entry).context("can't serialize index entry")?;
+        let index_file = self.index_file(&package.name);
+        if let Some(parent) = index_file.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        let mut index_file = fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(index_file)?;
+        writeln!(index_file, "{entry}")?;
+        debug!(
+            "added {}@{} to the local registry {:?}",
+            package.name, package.version, self.path
+        );
+        Ok(())
+    }
+
+    fn write_config(&self) -> anyhow::Result<()> {
+        let config_path = self.path.join("index").join("config.json");
+        if config_path.exists() {
+            return Ok(());
+        }
+        let crates_url = url::Url::from_directory_path(self.path.join("crates"))
+            .map_err(|()| anyhow::anyhow!("invalid local registry path {:?}", self.path))?;
+        let config = serde_json::json!({
+            "dl": format!("{crates_url}{{crate}}/{{crate}}-{{version}}.crate"),
+        });
+        if let Some(parent) = config_path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(config_path, config.to_string())?;
+        Ok(())
+    }
+
+    fn crate_file(&self, package: &Package) -> Utf8PathBuf {
+        self.path
+            .join("crates")
+            .join(package.name.as_str())
+            .join(format!("{}-{}.crate", package.name, package.version))
+    }
+
+    fn index_file(&self, crate_name: &str) -> Utf8PathBuf {
+        self.path
+            .join("index")
+            .join(index_relative_path(crate_name))
+    }
+}
+
+/// Path of the index file of the crate, relative to the root of the index.
+/// See the [index files layout](https://doc.rust-lang.org/cargo/reference/registry-index.html#index-files).
+fn index_relative_path(crate_name: &str) -> Utf8PathBuf {
+    let name = crate_name.to_lowercase();
+    match name.len() {
+        1 => Utf8PathBuf::from("1").join(&name),
+        2 => Utf8PathBuf::from("2").join(&name),
+        3 => Utf8PathBuf::from("3").join(&name[..1]).join(&name),
+        _ => Utf8PathBuf::from(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+fn index_entry<'a>(package: &'a Package, crate_content: &[u8]) -> IndexEntry<'a> {
+    let (features2, features): (BTreeMap<_, _>, BTreeMap<_, _>) = package
+        .features
+        .iter()
+        .map(|(name, values)| (name.as_str(), values.as_slice()))
+        // Features using the new syntax go in `features2`, so that old versions of cargo ignore them.
+        .partition(|(_, values)| {
+            values
+                .iter()
+                .any(|value| value.starts_with("dep:") || value.contains("?/"))
+        });
+    let deps = package
+        .dependencies
+        .iter()
+        // `cargo publish` removes the dev-dependencies without a version.
+        .filter(|dep| {
+            !(dep.kind == DependencyKind::Development
+                && dep.path.is_some()
+                && dep.req == VersionReq::STAR)
+        })
+        .map(|dep| IndexDependency {
+            name: dep.rename.as_deref().unwrap_or(&dep.name),
+            req: dep.req.to_string(),
+            features: &dep.features,
+            optional: dep.optional,
+            default_features: dep.uses_default_features,
+            target: dep.target.as_ref().map(ToString::to_string),
+            kind: dep.kind,
+            registry: match (&dep.registry, &dep.path) {
+                (Some(registry), _) => Some(registry.as_str()),
+                // Path dependencies are workspace packages, published to the local registry, too.
+                (None, Some(_)) => None,
+                (None, None) => Some(CRATES_IO_INDEX),
+            },
+            package: dep.rename.as_ref().map(|_| dep.name.as_str()),
+        })
+        .collect();
+    IndexEntry {
+        name: &package.name,
+        vers: package.version.to_string(),
+        deps,
+        cksum: format!("{:x}", Sha256::digest(crate_content)),
+        v: if features2.is_empty() { 1 } else { 2 },
+        features,
+        features2,
+        yanked: false,
+        links: package.links.as_deref(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cargo_metadata::semver::Version;
+    use fake_package::FakePackage;
+
+    use crate::fs_utils::Utf8TempDir;
+
+    use super::*;
+
+    #[test]
+    fn index_path_follows_cargo_layout() {
+        assert_eq!(index_relative_path("a"), "1/a");
+        assert_eq!(index_relative_path("ab"), "2/ab");
+        assert_eq!(index_relative_path("abc"), "3/a/abc");
+        assert_eq!(index_relative_path("Cargo"), "ca/rg/cargo");
+    }
+
+    #[test]
+    fn published_package_is_in_the_index() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let registry = LocalRegistry::new(temp_dir.path().join("registry"));
+        let crate_file = temp_dir.path().join("my_package-0.1.0.crate");
+        fs_err::write(&crate_file, "crate content").unwrap();
+        let package: Package = FakePackage::new("my_package").into();
+        let mut next_package = package.clone();
+        next_package.version = Version::new(0, 2, 0);
+
+        assert!(!registry.is_published(&package).unwrap());
+        registry.publish(&package, &crate_file).unwrap();
+        assert!(registry.is_published(&package).unwrap());
+        assert!(!registry.is_published(&next_package).unwrap());
+        assert!(registry.publish(&package, &crate_file).is_err());
+
+        let published_crate = registry
+            .path()
+            .join("crates/my_package/my_package-0.1.0.crate");
+        assert_eq!(
+            fs_err::read_to_string(published_crate).unwrap(),
+            "crate content"
+        );
+        let config = fs_err::read_to_string(registry.path().join("index/config.json")).unwrap();
+        assert!(config.contains("{crate}/{crate}-{version}.crate"));
+    }
+}
@@ -0,0 +1,366 @@
+//! Check the `Cargo.toml` metadata that crates.io validates when publishing,
+//! so that problems are reported before publishing anything.
+
+use std::collections::BTreeMap;
+
+use cargo_metadata::Package;
+use spdx::ParseMode;
+use tracing::warn;
+
+use crate::CRATES_IO_REGISTRY_NAME;
+
+/// Limits enforced by crates.io.
+const MAX_KEYWORDS: usize = 5;
+const MAX_CATEGORIES: usize = 5;
+const MAX_KEYWORD_LENGTH: usize = 20;
+const MAX_NAME_LENGTH: usize = 64;
+
+/// License expression syntax accepted by crates.io.
+const LICENSE_PARSE_MODE: ParseMode = ParseMode {
+    allow_lower_case_operators: false,
+    allow_slash_as_or_operator: true,
+    allow_imprecise_license_names: false,
+    allow_postfix_plus_on_gpl: true,
+};
+
+/// Category slugs of crates.io. See <https://crates.io/category_slugs>.
+const CRATES_IO_CATEGORIES: &[&str] = &[
+    "accessibility",
+    "aerospace",
+    "aerospace::drones",
+    "aerospace::protocols",
+    "aerospace::simulation",
+    "aerospace::space-protocols",
+    "aerospace::unmanned-aerial-vehicles",
+    "algorithms",
+    "api-bindings",
+    "asynchronous",
+    "authentication",
+    "caching",
+    "command-line-interface",
+    "command-line-utilities",
+    "compilers",
+    "compression",
+    "computer-vision",
+    "concurrency",
+    "config",
+    "cryptography",
+    "cryptography::cryptocurrencies",
+    "data-structures",
+    "database",
+    "database-implementations",
+    "date-and-time",
+    "development-tools",
+    "development-tools::build-utils",
+    "development-tools::cargo-plugins",
+    "development-tools::debugging",
+    "development-tools::ffi",
+    "development-tools::procedural-macro-helpers",
+    "development-tools::profiling",
+    "development-tools::testing",
+    "email",
+    "embedded",
+    "emulators",
+    "encoding",
+    "external-ffi-bindings",
+    "filesystem",
+    "finance",
+    "game-development",
+    "game-engines",
+    "games",
+    "graphics",
+    "gui",
+    "hardware-support",
+    "internationalization",
+    "localization",
+    "mathematics",
+    "memory-management",
+    "multimedia",
+    "multimedia::audio",
+    "multimedia::encoding",
+    "multimedia::images",
+    "multimedia::video",
+    "network-programming",
+    "no-std",
+    "no-std::no-alloc",
+    "os",
+    "os::android-apis",
+    "os::freebsd-apis",
+    "os::linux-apis",
+    "os::macos-apis",
+    "os::unix-apis",
+    "os::windows-apis",
+    "parser-implementations",
+    "parsing",
+    "rendering",
+    "rendering::data-formats",
+    "rendering::engine",
+    "rendering::graphics-api",
+    "rust-patterns",
+    "science",
+    "science::bioinformatics",
+    "science::bioinformatics::genomics",
+    "science::bioinformatics::proteomics",
+    "science::bioinformatics::sequence-analysis",
+    "science::geo",
+    "science::neuroscience",
+    "science::robotics",
+    "simulation",
+    "template-engine",
+    "text-editors",
+    "text-processing",
+    "value-formatting",
+    "virtualization",
+    "visualization",
+    "wasm",
+    "web-programming",
+    "web-programming::http-client",
+    "web-programming::http-server",
+    "web-programming::websocket",
+];
+
+/// Whether the package is published to crates.io, according to the `publish` field of its manifest.
+pub fn is_published_to_crates_io(package: &Package) -> bool {
+    package.publish.as_ref().is_none_or(|registries| {
+        registries
+            .iter()
+            .any(|registry| registry == CRATES_IO_REGISTRY_NAME)
+    })
+}
+
+/// Check the metadata of the packages that are going to be published to crates.io.
+/// The error contains the problems of all the packages.
+/// Metadata that crates.io accepts with a warning is logged instead.
+pub fn lint_metadata(packages: &[&Package]) -> anyhow::Result<()> {
+    for package in packages {
+        for warning in package_warnings(package) {
+            warn!("`{}`: {warning}", package.name);
+        }
+    }
+    let mut problems: Vec<String> = packages
+        .iter()
+        .flat_map(|package| {
+            package_problems(package)
+                .into_iter()
+                .map(|problem| format!("- `{}`: {problem}", package.name))
+        })
+        .collect();
+    problems.extend(name_collisions(packages));
+    if problems.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "{} problem(s) found in the Cargo.toml metadata. Publishing the packages would fail:\n{}
+See https://doc.rust-lang.org/cargo/reference/manifest.html
+Note: to disable this check, set `metadata_lint = false` in the `[workspace]` section of the release-plz config.",
+        problems.len(),
+        problems.join("\n")
+    )
+}
+
+fn package_problems(package: &Package) -> Vec<String> {
+    let mut problems = vec![];
+    problems.extend(name_problem(&package.name));
+    if package
+        .description
+        .as_deref()
+        .is_none_or(|d| d.trim().is_empty())
+    {
+        problems.push("`description` is missing".to_string());
+    }
+    problems.extend(license_problems(package));
+    problems.extend(keyword_problems(&package.keywords));
+    if package.categories.len() > MAX_CATEGORIES {
+        problems.push(format!(
+            "there are {} categories, but crates.io accepts at most {MAX_CATEGORIES}",
+            package.categories.len()
+        ));
+    }
+    for (field, value) in [
+        ("homepage", &package.homepage),
+        ("documentation", &package.documentation),
+        ("repository", &package.repository),
+    ] {
+        if let Some(value) = value.as_deref().filter(|value| !is_http_url(value)) {
+            problems.push(format!("`{field}` `{value}` is not a valid http(s) URL"));
+        }
+    }
+    if let Some(readme) = package.readme().filter(|readme| !readme.is_file()) {
+        problems.push(format!("`readme` file {readme:?} doesn't exist"));
+    }
+    problems
+}
+
+fn name_problem(name: &str) -> Option<String> {
+    if name.len() > MAX_NAME_LENGTH {
+        return Some(format!(
+            "the name is longer than {MAX_NAME_LENGTH} characters"
+        ));
+    }
+    let starts_with_letter = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+    let has_valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    (!starts_with_letter || !has_valid_chars).then(|| {
+        "the name must start with a letter and contain only letters, numbers, `-` or `_`"
+            .to_string()
+    })
+}
+
+fn license_problems(package: &Package) -> Vec<String> {
+    let mut problems = vec![];
+    match (&package.license, package.license_file()) {
+        (None, None) => problems.push("`license` or `license-file` is missing".to_string()),
+        (Some(license), _) => {
+            if let Err(e) = spdx::Expression::parse_mode(license, LICENSE_PARSE_MODE) {
+                problems.push(format!(
+                    "`license` `{license}` is not a valid SPDX expression: {}",
+                    e.reason
+                ));
+            }
+        }
+        (None, Some(_)) => {}
+    }
+    if let Some(license_file) = package.license_file().filter(|file| !file.is_file()) {
+        problems.push(format!("`license-file` {license_file:?} doesn't exist"));
+    }
+    problems
+}
+
+fn keyword_problems(keywords: &[String]) -> Vec<String> {
+    let mut problems = vec![];
+    if keywords.len() > MAX_KEYWORDS {
+        problems.push(format!(
+            "there are {} keywords, but crates.io accepts at most {MAX_KEYWORDS}",
+            keywords.len()
+        ));
+    }
+    for keyword in keywords {
+        if keyword.len() > MAX_KEYWORD_LENGTH {
+            problems.push(format!(
+                "keyword `{keyword}` is longer than {MAX_KEYWORD_LENGTH} characters"
+            ));
+        }
+        let starts_with_alphanumeric = keyword
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric());
+        let has_valid_chars = keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ['-', '_', '+'].contains(&c));
+        if !starts_with_alphanumeric || !has_valid_chars {
+            problems.push(format!(
+                "keyword `{keyword}` must start with a letter or a number and contain only letters, numbers, `-`, `_` or `+`"
+            ));
+        }
+    }
+    problems
+}
+
+/// crates.io publishes the package anyway, but it ignores the unknown categories.
+fn package_warnings(package: &Package) -> Vec<String> {
+    package
+        .categories
+        .iter()
+        .filter(|category| !CRATES_IO_CATEGORIES.contains(&category.as_str()))
+        .map(|category| {
+            format!(
+                "category `{category}` is not a crates.io category slug, so crates.io ignores it. See https://crates.io/category_slugs"
+            )
+        })
+        .collect()
+}
+
+fn is_http_url(value: &str) -> bool {
+    url::Url::parse(value).is_ok_and(|url| ["http", "https"].contains(&url.scheme()))
+}
+
+/// crates.io considers names that differ only by case, `-` or `_` as the same crate.
+fn name_collisions(packages: &[&Package]) -> Vec<String> {
+    let mut normalized_names: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for package in packages {
+        normalized_names
+            .entry(package.name.to_lowercase().replace('-', "_"))
+            .or_default()
+            .push(&package.name);
+    }
+    normalized_names
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .map(|names| {
+            format!(
+                "- packages `{}` have the same name on crates.io, because crates.io ignores case and treats `-` and `_` as the same character",
+                names.join("`, `")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_package::FakePackage;
+
+    use super::*;
+
+    fn valid_package(name: &str) -> Package {
+        let mut package: Package = FakePackage::new(name).into();
+        package.description = Some("my package".to_string());
+        package.license = Some("MIT OR Apache-2.0".to_string());
+        package
+    }
+
+    #[test]
+    fn valid_package_has_no_problems() {
+        let mut package = valid_package("my_package");
+        package.keywords = vec!["cli".to_string(), "c++".to_string()];
+        package.categories = vec!["development-tools::cargo-plugins".to_string()];
+        package.homepage = Some("https://example.com".to_string());
+        assert_eq!(package_problems(&package), Vec::<String>::new());
+        assert_eq!(package_warnings(&package), Vec::<String>::new());
+        assert!(lint_metadata(&[&package]).is_ok());
+    }
+
+    #[test]
+    fn unknown_categories_are_warnings() {
+        let mut package = valid_package("my_package");
+        package.categories = vec!["command-line-tools".to_string()];
+        assert_eq!(package_problems(&package), Vec::<String>::new());
+        expect_test::expect![[r#"
+            [
+                "category `command-line-tools` is not a crates.io category slug, so crates.io ignores it. See https://crates.io/category_slugs",
+            ]
+        "#]]
+        .assert_debug_eq(&package_warnings(&package));
+        assert!(lint_metadata(&[&package]).is_ok());
+    }
+
+    #[test]
+    fn all_problems_are_reported() {
+        let mut package = valid_package("my_package");
+        package.description = None;
+        package.license = Some("MIT or Apache".to_string());
+        package.keywords = ["a", "b", "c", "d", "e", "-f"].map(String::from).to_vec();
+        package.documentation = Some("docs.rs/my_package".to_string());
+        let problems = package_problems(&package);
+        expect_test::expect![[r#"
+            [
+                "`description` is missing",
+                "`license` `MIT or Apache` is not a valid SPDX expression: unknown term",
+                "there are 6 keywords, but crates.io accepts at most 5",
+                "keyword `-f` must start with a letter or a number and contain only letters, numbers, `-`, `_` or `+`",
+                "`documentation` `docs.rs/my_package` is not a valid http(s) URL",
+            ]
+        "#]]
+        .assert_debug_eq(&problems);
+    }
+
+    #[test]
+    fn colliding_names_are_reported() {
+        let first = valid_package("my-package");
+        let second = valid_package("My_Package");
+        let other = valid_package("other");
+        let collisions = name_collisions(&[&first, &second, &other]);
+        assert_eq!(collisions.len(), 1);
+        assert!(collisions[0].contains("`my-package`, `My_Package`"));
+    }
+}
@@ -0,0 +1,75 @@
+//! Milestones named after the released versions, e.g. `my_package v1.2.0`.
+
+use anyhow::Context as _;
+use tracing::{debug, info};
+
+use crate::{
+    GitClient,
+    tera::{render_template, tera_context},
+};
+
+/// Render the template of the milestone title.
+pub(crate) fn milestone_title(
+    template: &str,
+    package_name: &str,
+    version: &str,
+) -> anyhow::Result<String> {
+    let context = tera_context(package_name, version);
+    render_template(template, &context, "milestone")
+}
+
+/// Close the milestone, if it exists and it's open.
+pub(crate) async fn close_milestone(git_client: &GitClient, title: &str) -> anyhow::Result<()> {
+    let milestone = git_client
+        .find_milestone(title)
+        .await
+        .with_context(|| format!("can't retrieve milestone {title}"))?;
+    match milestone {
+        Some(milestone) if !milestone.is_closed() => git_client
+            .close_milestone(&milestone)
+            .await
+            .with_context(|| format!("can't close milestone {title}")),
+        Some(_) => {
+            debug!("milestone {title} is already closed");
+            Ok(())
+        }
+        None => {
+            info!("milestone {title} doesn't exist, so there's nothing to close");
+            Ok(())
+        }
+    }
+}
+
+/// Assign the PR to the milestone, creating the milestone if it doesn't exist.
+pub(crate) async fn assign_pr_to_milestone(
+    git_client: &GitClient,
+    pr_number: u64,
+    title: &str,
+) -> anyhow::Result<()> {
+    let milestone = match git_client
+        .find_milestone(title)
+        .await
+        .with_context(|| format!("can't retrieve milestone {title}"))?
+    {
+        Some(milestone) => milestone,
+        None => git_client
+            .create_milestone(title)
+            .await
+            .with_context(|| format!("can't create milestone {title}"))?,
+    };
+    git_client
+        .set_pr_milestone(pr_number, &milestone)
+        .await
+        .with_context(|| format!("can't assign PR #{pr_number} to milestone {title}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milestone_title_is_rendered() {
+        let title = milestone_title("{{ package }} v{{ version }}", "my_package", "1.2.0").unwrap();
+        assert_eq!(title, "my_package v1.2.0");
+    }
+}
@@ -1,7 +1,7 @@
 use crate::update_request::UpdateRequest;
 use crate::updater::Updater;
 use crate::{
-    PackagesUpdate, Project,
+    PackageContentsDiff, PackagesUpdate, Project,
     changelog_parser::{self, ChangelogRelease},
     copy_dir::copy_dir,
     fs_utils::{Utf8TempDir, strip_prefix},
@@ -60,13 +60,16 @@ impl ReleaseMetadataBuilder for UpdateRequest {
 #[instrument(skip_all)]
 pub async fn next_versions(input: &UpdateRequest) -> anyhow::Result<(PackagesUpdate, TempRepo)> {
     let overrides = input.packages_config().overridden_packages();
-    let local_project = Project::new(
+    let mut local_project = Project::new(
         input.local_manifest(),
         input.single_package(),
         &overrides,
         input.cargo_metadata(),
         input,
     )?;
+    if let Some(packages) = input.packages() {
+        local_project.retain_packages(packages);
+    }
     let updater = Updater {
         project: &local_project,
         req: input,
@@ -133,6 +136,9 @@ pub struct UpdateResult {
     /// New changelog.
     pub changelog: Option<String>,
     pub semver_check: SemverCheck,
+    /// How the files of the package changed since the version published in the registry.
+    /// [`Option::None`] if release-plz didn't compare the files.
+    pub package_contents: Option<PackageContentsDiff>,
 }
 
 impl UpdateResult {
@@ -0,0 +1,319 @@
+use std::{collections::BTreeMap, env::VarError};
+
+use anyhow::Context as _;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use serde::Serialize;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::tera::render_template;
+
+/// Name of the tera variable containing the event that triggered the notification.
+const EVENT_VAR: &str = "event";
+
+/// Command that triggers a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    /// `release-plz release` released at least one package.
+    Release,
+    /// `release-plz release-pr` opened or updated the release PR.
+    ReleasePr,
+}
+
+impl NotificationEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Release => "release",
+            Self::ReleasePr => "release-pr",
+        }
+    }
+}
+
+/// Webhook called with a JSON payload after a command completes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    url: Url,
+    /// Events that trigger the notification.
+    /// If empty, all the events trigger the notification.
+    events: Vec<NotificationEvent>,
+    /// Tera template of the payload.
+    /// If [`Option::None`], the payload is the JSON output of the command.
+    payload_template: Option<String>,
+    /// `<header name, name of the environment variable containing the header value>`
+    headers: BTreeMap<String, String>,
+}
+
+impl Notification {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            events: vec![],
+            payload_template: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_events(mut self, events: Vec<NotificationEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn with_payload_template(mut self, payload_template: Option<String>) -> Self {
+        self.payload_template = payload_template;
+        self
+    }
+
+    pub fn with_headers(mut self, headers: BTreeMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn is_triggered_by(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    /// Render the payload of the notification.
+    /// `output` is the JSON output of the command.
+    fn payload(
+        &self,
+        event: NotificationEvent,
+        output: &serde_json::Value,
+    ) -> anyhow::Result<String> {
+        let Some(template) = &self.payload_template else {
+            return Ok(output.to_string());
+        };
+        let mut context =
+            tera::Context::from_value(output.clone()).context("invalid notification context")?;
+        context.insert(EVENT_VAR, event.name());
+        let payload = render_template(template, &context, "notification_payload")?;
+        serde_json::from_str::<serde_json::Value>(&payload)
+            .with_context(|| format!("the rendered payload isn't valid JSON: {payload}"))?;
+        Ok(payload)
+    }
+
+    /// Headers of the request.
+    /// `read_env` returns the value of an environment variable.
+    fn headers(
+        &self,
+        read_env: &impl Fn(&str) -> Result<String, VarError>,
+    ) -> anyhow::Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        for (name, env_var) in &self.headers {
+            let value = read_env(env_var).with_context(|| {
+                format!("can't read environment variable {env_var} of header {name}")
+            })?;
+            crate::add_secret(&value);
+            let name = HeaderName::try_from(name.as_str())
+                .with_context(|| format!("invalid header name {name}"))?;
+            let mut value = HeaderValue::try_from(value)
+                .with_context(|| format!("invalid value of header {name}"))?;
+            // The header can contain a secret, so don't print it in the logs.
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+
+    async fn send(
+        &self,
+        client: &ClientWithMiddleware,
+        event: NotificationEvent,
+        output: &serde_json::Value,
+        read_env: &impl Fn(&str) -> Result<String, VarError>,
+    ) -> anyhow::Result<()> {
+        let payload = self.payload(event, output)?;
+        client
+            .post(self.url.clone())
+            .headers(self.headers(read_env)?)
+            .body(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Send the notifications triggered by `event`.
+/// `output` is the output of the command, available in the payload template.
+///
+/// A failed notification doesn't return an error, because the command already completed.
+pub async fn send_notifications(
+    notifications: &[Notification],
+    event: NotificationEvent,
+    output: &impl Serialize,
+) -> anyhow::Result<()> {
+    send_notifications_with_env(notifications, event, output, &|var| std::env::var(var)).await
+}
+
+/// Like [`send_notifications`], but read the environment variables of the headers
+/// with `read_env`.
+async fn send_notifications_with_env(
+    notifications: &[Notification],
+    event: NotificationEvent,
+    output: &impl Serialize,
+    read_env: &impl Fn(&str) -> Result<String, VarError>,
+) -> anyhow::Result<()> {
+    let notifications: Vec<&Notification> = notifications
+        .iter()
+        .filter(|n| n.is_triggered_by(event))
+        .collect();
+    if notifications.is_empty() {
+        return Ok(());
+    }
+    let output = serde_json::to_value(output).context("can't serialize notification output")?;
+    let client = notification_client()?;
+    for notification in notifications {
+        info!(
+            "sending {} notification to {}",
+            event.name(),
+            notification.url
+        );
+        if let Err(e) = notification.send(&client, event, &output, read_env).await {
+            warn!("notification to {} failed: {e:?}", notification.url);
+        }
+    }
+    Ok(())
+}
+
+fn notification_client() -> anyhow::Result<ClientWithMiddleware> {
+    let reqwest_client = reqwest::Client::builder()
+        .user_agent("release-plz")
+        .build()
+        .context("can't build notification client")?;
+    let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+    let client = ClientBuilder::new(reqwest_client)
+        // Retry failed requests.
+        .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        .build();
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, header, method, path},
+    };
+
+    use super::*;
+
+    fn output() -> serde_json::Value {
+        serde_json::json!({
+            "releases": [{
+                "package_name": "my_package",
+                "version": "0.1.0",
+            }]
+        })
+    }
+
+    fn notification(server: &MockServer) -> Notification {
+        Notification::new(format!("{}/hook", server.uri()).parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn rendered_payload_is_sent_with_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(header("X-Token", "secret"))
+            .and(body_json(serde_json::json!({
+                "text": "release: my_package 0.1.0"
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notification = notification(&server)
+            .with_payload_template(Some(
+                r#"{"text": "{{ event }}: {% for r in releases %}{{ r.package_name }} {{ r.version }}{% endfor %}"}"#
+                    .to_string(),
+            ))
+            .with_headers(BTreeMap::from([(
+                "X-Token".to_string(),
+                "NOTIFICATION_TOKEN".to_string(),
+            )]));
+        let read_env = |var: &str| match var {
+            "NOTIFICATION_TOKEN" => Ok("secret".to_string()),
+            _ => Err(VarError::NotPresent),
+        };
+        send_notifications_with_env(
+            &[notification],
+            NotificationEvent::Release,
+            &output(),
+            &read_env,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn default_payload_is_command_output() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(header("content-type", "application/json"))
+            .and(body_json(output()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notification = notification(&server);
+        send_notifications(&[notification], NotificationEvent::Release, &output())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_request_is_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notification = notification(&server);
+        send_notifications(&[notification], NotificationEvent::Release, &output())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn notification_is_not_sent_for_other_events() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let notification = notification(&server).with_events(vec![NotificationEvent::ReleasePr]);
+        send_notifications(&[notification], NotificationEvent::Release, &output())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_json_payload_is_rejected() {
+        let notification = Notification::new("http://localhost/hook".parse().unwrap())
+            .with_payload_template(Some("{{ event }}".to_string()));
+        let error = notification
+            .payload(NotificationEvent::Release, &output())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the rendered payload isn't valid JSON: release"
+        );
+    }
+}
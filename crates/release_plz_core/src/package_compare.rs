@@ -31,25 +31,10 @@ pub fn are_packages_equal(
         return Ok(false);
     }
 
-    // When a package is published to a cargo registry, the original `Cargo.toml` file is stored as `Cargo.toml.orig`.
-    // We need to rename it to `Cargo.toml.orig.orig`, because this name is reserved, and `cargo package` will fail if it exists.
-    rename(
-        registry_package.join("Cargo.toml.orig"),
-        registry_package.join("Cargo.toml.orig.orig"),
-    )?;
-
     let local_package_files = get_cargo_package_files(local_package).with_context(|| {
         format!("cannot determine packaged files of local package {local_package:?}")
     })?;
-    let registry_package_files = get_cargo_package_files(registry_package).with_context(|| {
-        format!("cannot determine packaged files of registry package {registry_package:?}")
-    })?;
-
-    // Rename the file to the original name.
-    rename(
-        registry_package.join("Cargo.toml.orig.orig"),
-        registry_package.join("Cargo.toml.orig"),
-    )?;
+    let registry_package_files = get_registry_package_files(registry_package)?;
 
     let local_files = local_package_files
         .iter()
@@ -102,6 +87,27 @@ fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> anyhow::Result<()> {
     fs_err::rename(from, to).with_context(|| format!("cannot rename {from:?} to {to:?}"))
 }
 
+/// Run `cargo package --list` in a package downloaded from a cargo registry.
+pub fn get_registry_package_files(registry_package: &Utf8Path) -> anyhow::Result<Vec<Utf8PathBuf>> {
+    // When a package is published to a cargo registry, the original `Cargo.toml` file is stored as `Cargo.toml.orig`.
+    // We need to rename it to `Cargo.toml.orig.orig`, because this name is reserved, and `cargo package` will fail if it exists.
+    rename(
+        registry_package.join("Cargo.toml.orig"),
+        registry_package.join("Cargo.toml.orig.orig"),
+    )?;
+
+    let registry_package_files = get_cargo_package_files(registry_package).with_context(|| {
+        format!("cannot determine packaged files of registry package {registry_package:?}")
+    });
+
+    // Rename the file to the original name.
+    rename(
+        registry_package.join("Cargo.toml.orig.orig"),
+        registry_package.join("Cargo.toml.orig"),
+    )?;
+    registry_package_files
+}
+
 pub fn get_cargo_package_files(package: &Utf8Path) -> anyhow::Result<Vec<Utf8PathBuf>> {
     // we use `--allow-dirty` because we have `Cargo.toml.orig.orig`, which is an uncommitted change.
     let args = ["package", "--list", "--quiet", "--allow-dirty"];
@@ -0,0 +1,217 @@
+//! Files that `cargo package` puts in a package.
+//!
+//! Release-plz reports how they changed since the previous release in the release PR,
+//! and checks them against the configured limits before publishing.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context as _;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::{get_cargo_package_files, get_registry_package_files};
+
+/// Files added by `cargo package` or by release-plz. They aren't part of the package sources.
+const GENERATED_FILES: &[&str] = &[
+    "Cargo.toml.orig",
+    "Cargo.toml.orig.orig",
+    ".cargo_vcs_info.json",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageContents {
+    /// Files of the package, relative to the package root.
+    files: BTreeSet<Utf8PathBuf>,
+    /// Sum of the sizes of the files, in bytes.
+    /// It's the size of the package before `cargo package` compresses it.
+    size: u64,
+}
+
+impl PackageContents {
+    /// Read the files of a local package.
+    pub fn from_local_package(package_path: &Utf8Path) -> anyhow::Result<Self> {
+        let files = get_cargo_package_files(package_path).with_context(|| {
+            format!("cannot determine packaged files of local package {package_path:?}")
+        })?;
+        Self::new(package_path, files)
+    }
+
+    /// Read the files of a package downloaded from a cargo registry.
+    pub fn from_registry_package(package_path: &Utf8Path) -> anyhow::Result<Self> {
+        let files = get_registry_package_files(package_path)?;
+        Self::new(package_path, files)
+    }
+
+    fn new(package_path: &Utf8Path, files: Vec<Utf8PathBuf>) -> anyhow::Result<Self> {
+        let files: BTreeSet<Utf8PathBuf> = files
+            .into_iter()
+            .filter(|file| !GENERATED_FILES.contains(&file.as_str()))
+            .collect();
+        let mut size = 0;
+        for file in &files {
+            let path = package_path.join(file);
+            // `cargo package --list` can return files that don't exist locally,
+            // such as the `Cargo.lock` file of a package in a workspace.
+            if path.is_file() {
+                size += fs_err::metadata(&path)?.len();
+            }
+        }
+        Ok(Self { files, size })
+    }
+
+    /// Compare the package with its previous release.
+    /// If [`Option::None`], the package was never released.
+    pub fn diff(&self, previous: Option<&Self>) -> PackageContentsDiff {
+        let (added_files, removed_files) = match previous {
+            Some(previous) => (
+                self.files.difference(&previous.files).cloned().collect(),
+                previous.files.difference(&self.files).cloned().collect(),
+            ),
+            None => (vec![], vec![]),
+        };
+        PackageContentsDiff {
+            size: self.size,
+            previous_size: previous.map(|p| p.size),
+            added_files,
+            removed_files,
+        }
+    }
+
+    /// Check the package against the limits configured by the user.
+    /// Return the list of the violated limits.
+    pub fn problems(&self, limits: &PackageContentsLimits) -> anyhow::Result<Vec<String>> {
+        let mut problems = vec![];
+        if let Some(max_size) = limits.max_size.filter(|max_size| self.size > *max_size) {
+            problems.push(format!(
+                "the package size is {} bytes, but `max_package_size` is {max_size} bytes",
+                self.size
+            ));
+        }
+        let forbidden_files = limits
+            .forbidden_files
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .with_context(|| format!("invalid forbidden file pattern `{pattern}`"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for file in &self.files {
+            if let Some(pattern) = forbidden_files
+                .iter()
+                .find(|pattern| pattern.matches_path(file.as_std_path()))
+            {
+                problems.push(format!(
+                    "file `{file}` matches the forbidden pattern `{pattern}`"
+                ));
+            }
+        }
+        Ok(problems)
+    }
+}
+
+/// How the files of a package changed since the previous release.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackageContentsDiff {
+    /// Size of the files of the package, in bytes.
+    pub size: u64,
+    /// Size of the files of the previous release, in bytes.
+    /// [`Option::None`] if the package was never released.
+    pub previous_size: Option<u64>,
+    /// Files that the previous release didn't contain.
+    pub added_files: Vec<Utf8PathBuf>,
+    /// Files of the previous release that the package doesn't contain anymore.
+    pub removed_files: Vec<Utf8PathBuf>,
+}
+
+/// Limits that the files of a package must respect to be published.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageContentsLimits {
+    /// Maximum size of the files of the package, in bytes.
+    max_size: Option<u64>,
+    /// Glob patterns of the files that the package must not contain.
+    /// The patterns are relative to the package root.
+    forbidden_files: Vec<String>,
+}
+
+impl PackageContentsLimits {
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn with_forbidden_files(mut self, forbidden_files: Vec<String>) -> Self {
+        self.forbidden_files = forbidden_files;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_size.is_none() && self.forbidden_files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(files: &[&str], size: u64) -> PackageContents {
+        PackageContents {
+            files: files.iter().map(Utf8PathBuf::from).collect(),
+            size,
+        }
+    }
+
+    #[test]
+    fn diff_lists_added_and_removed_files() {
+        let previous = contents(&["Cargo.toml", "src/lib.rs", "src/old.rs"], 100);
+        let current = contents(&["Cargo.toml", "src/lib.rs", "tests/fixture.bin"], 200);
+        let diff = current.diff(Some(&previous));
+        assert_eq!(
+            diff,
+            PackageContentsDiff {
+                size: 200,
+                previous_size: Some(100),
+                added_files: vec!["tests/fixture.bin".into()],
+                removed_files: vec!["src/old.rs".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn limits_are_checked() {
+        let current = contents(&["Cargo.toml", "src/lib.rs", "tests/fixtures/big.bin"], 200);
+        let limits = PackageContentsLimits::default()
+            .with_max_size(100)
+            .with_forbidden_files(vec![
+                "tests/fixtures/**".to_string(),
+                "*.tar.gz".to_string(),
+            ]);
+        expect_test::expect![[r#"
+            [
+                "the package size is 200 bytes, but `max_package_size` is 100 bytes",
+                "file `tests/fixtures/big.bin` matches the forbidden pattern `tests/fixtures/**`",
+            ]
+        "#]]
+        .assert_debug_eq(&current.problems(&limits).unwrap());
+        let no_limits = PackageContentsLimits::default();
+        assert!(current.problems(&no_limits).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let limits = PackageContentsLimits::default().with_forbidden_files(vec!["[".to_string()]);
+        let error = contents(&[], 0).problems(&limits).unwrap_err();
+        assert_eq!(error.to_string(), "invalid forbidden file pattern `[`");
+    }
+
+    #[test]
+    fn generated_files_are_ignored() {
+        let temp_dir = crate::fs_utils::Utf8TempDir::new().unwrap();
+        fs_err::write(temp_dir.path().join("Cargo.toml"), "12345").unwrap();
+        fs_err::write(temp_dir.path().join("Cargo.toml.orig"), "12345").unwrap();
+        let files = ["Cargo.toml", "Cargo.toml.orig", "Cargo.lock"]
+            .map(Utf8PathBuf::from)
+            .to_vec();
+        let contents = PackageContents::new(temp_dir.path(), files).unwrap();
+        assert_eq!(contents, self::contents(&["Cargo.lock", "Cargo.toml"], 5));
+    }
+}
@@ -34,6 +34,16 @@ pub const DEFAULT_PR_BODY_TEMPLATE: &str = r#"
 ```text
 {{ release.breaking_changes }}
 ```{% endif %}{% endfor %}
+{%- for release in releases %}{% set contents = release.package_contents %}{% if contents %}{% if contents.previous_size is number and (contents.added_files or contents.removed_files or contents.size != contents.previous_size) %}
+
+### 📦 `{{ release.package }}` package contents
+
+Size: {{ contents.previous_size | filesizeformat }} -> {{ contents.size | filesizeformat }}{% if contents.added_files or contents.removed_files %}
+
+```diff
+{% for file in contents.added_files %}+ {{ file }}
+{% endfor %}{% for file in contents.removed_files %}- {{ file }}
+{% endfor %}```{% endif %}{% endif %}{% endif %}{% endfor %}
 {% if changes %}
 <details><summary><i><b>Changelog</b></i></summary><p>
 {{ changes }}
@@ -50,6 +60,38 @@ pub struct Pr {
     pub body: String,
     pub draft: bool,
     pub labels: Vec<String>,
+    pub reviewers: PrReviewers,
+}
+
+/// Reviewers and assignees of a PR.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrReviewers {
+    /// Users requested to review the PR.
+    pub reviewers: Vec<String>,
+    /// Teams requested to review the PR.
+    pub team_reviewers: Vec<String>,
+    /// Users assigned to the PR.
+    pub assignees: Vec<String>,
+}
+
+impl PrReviewers {
+    pub fn is_empty(&self) -> bool {
+        self.reviewers.is_empty() && self.team_reviewers.is_empty() && self.assignees.is_empty()
+    }
+
+    /// Add the users and teams of `other` that aren't already present.
+    pub fn extend(&mut self, other: &Self) {
+        let extend = |names: &mut Vec<String>, others: &[String]| {
+            for name in others {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        };
+        extend(&mut self.reviewers, &other.reviewers);
+        extend(&mut self.team_reviewers, &other.team_reviewers);
+        extend(&mut self.assignees, &other.assignees);
+    }
 }
 
 impl Pr {
@@ -72,6 +114,7 @@ impl Pr {
             body: pr_body(packages_to_update, body_template)?,
             draft: false,
             labels: vec![],
+            reviewers: PrReviewers::default(),
         };
         Ok(pr)
     }
@@ -85,8 +128,22 @@ impl Pr {
         self.labels = labels;
         self
     }
+
+    pub fn with_reviewers(mut self, reviewers: PrReviewers) -> Self {
+        self.reviewers = reviewers;
+        self
+    }
+
+    /// Show the banner at the top of the PR body.
+    pub fn with_banner(mut self, banner: &str) -> Self {
+        self.body = trim_pr_body(format!("{banner}\n{}", self.body));
+        self
+    }
 }
 
+/// Format of the timestamp at the end of the release branch name.
+const RELEASE_BRANCH_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%SZ";
+
 fn release_branch(prefix: &str) -> String {
     let now = chrono::offset::Utc::now();
     // Convert to a string of format "2018-01-26T18:30:09Z".
@@ -96,6 +153,15 @@ fn release_branch(prefix: &str) -> String {
     format!("{prefix}{now}")
 }
 
+/// Returns `true` if the branch is a release branch with exactly this prefix.
+/// E.g. `release-plz-my-package-2018-01-26T18-30-09Z` has the prefix `release-plz-my-package-`,
+/// but not the prefix `release-plz-my-`.
+pub(crate) fn is_release_branch(branch: &str, prefix: &str) -> bool {
+    branch.strip_prefix(prefix).is_some_and(|timestamp| {
+        chrono::NaiveDateTime::parse_from_str(timestamp, RELEASE_BRANCH_TIMESTAMP_FORMAT).is_ok()
+    })
+}
+
 fn pr_title(
     packages_to_update: &PackagesUpdate,
     project_contains_multiple_pub_packages: bool,
@@ -189,3 +255,111 @@ fn trim_pr_body(body: String) -> String {
         body
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_branch_matches_only_its_prefix() {
+        let branch = release_branch("release-plz-my-package-");
+        assert!(is_release_branch(&branch, "release-plz-my-package-"));
+        assert!(!is_release_branch(&branch, "release-plz-my-"));
+        assert!(!is_release_branch(&branch, "release-plz-"));
+    }
+
+    #[test]
+    fn changed_package_contents_are_in_the_body() {
+        let releases: Vec<ReleaseInfo> = serde_json::from_value(serde_json::json!([
+            {
+                "package": "my_package",
+                "title": null,
+                "changelog": null,
+                "previous_version": "0.1.0",
+                "next_version": "0.1.1",
+                "breaking_changes": null,
+                "semver_check": "skipped",
+                "package_contents": {
+                    "size": 2048,
+                    "previous_size": 1024,
+                    "added_files": ["tests/fixture.bin"],
+                    "removed_files": ["src/old.rs"]
+                }
+            }
+        ]))
+        .unwrap();
+        let body = render_pr_body(&releases, DEFAULT_PR_BODY_TEMPLATE).unwrap();
+        expect_test::expect![[r#"
+
+
+
+            ## 🤖 New release
+
+            * `my_package`: 0.1.0 -> 0.1.1
+
+            ### 📦 `my_package` package contents
+
+            Size: 1 kB -> 2 kB
+
+            ```diff
+            + tests/fixture.bin
+            - src/old.rs
+            ```
+
+            <details><summary><i><b>Changelog</b></i></summary><p>
+
+
+
+            </p></details>
+
+            ---
+            This PR was generated with [release-plz](https://github.com/release-plz/release-plz/)."#]]
+        .assert_eq(&body);
+    }
+
+    fn body_of_package_contents(package_contents: &serde_json::Value) -> String {
+        let releases: Vec<ReleaseInfo> = serde_json::from_value(serde_json::json!([
+            {
+                "package": "my_package",
+                "title": null,
+                "changelog": null,
+                "previous_version": "0.1.0",
+                "next_version": "0.1.1",
+                "breaking_changes": null,
+                "semver_check": "skipped",
+                "package_contents": package_contents
+            }
+        ]))
+        .unwrap();
+        render_pr_body(&releases, DEFAULT_PR_BODY_TEMPLATE).unwrap()
+    }
+
+    #[test]
+    fn package_size_change_is_in_the_body() {
+        let body = body_of_package_contents(&serde_json::json!({
+            "size": 2048,
+            "previous_size": 1024,
+            "added_files": [],
+            "removed_files": []
+        }));
+        assert!(body.contains("package contents\n\nSize: 1 kB -> 2 kB\n\n<details>"));
+    }
+
+    #[test]
+    fn package_contents_without_changes_or_previous_release_are_not_in_the_body() {
+        let unchanged = body_of_package_contents(&serde_json::json!({
+            "size": 2048,
+            "previous_size": 2048,
+            "added_files": [],
+            "removed_files": []
+        }));
+        assert!(!unchanged.contains("package contents"));
+        let first_release = body_of_package_contents(&serde_json::json!({
+            "size": 2048,
+            "previous_size": null,
+            "added_files": [],
+            "removed_files": []
+        }));
+        assert!(!first_release.contains("package contents"));
+    }
+}
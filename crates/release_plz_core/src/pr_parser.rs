@@ -26,6 +26,20 @@ pub fn prs_from_text(text: &str) -> Vec<Pr> {
         .collect()
 }
 
+/// Parse the numbers of the issues that a PR closes from its body,
+/// e.g. `Closes #12` or `fixes: #34`.
+pub fn closed_issues(pr_body: &str) -> Vec<u64> {
+    let re = Regex::new(r"(?i)\b(?:close[sd]?|fix(?:e[sd])?|resolve[sd]?):?\s+#(\d+)\b").unwrap();
+
+    let mut issues: Vec<u64> = re
+        .captures_iter(pr_body)
+        .filter_map(|capture| capture.get(1)?.as_str().parse().ok())
+        .collect();
+    issues.sort_unstable();
+    issues.dedup();
+    issues
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,4 +94,10 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn closed_issues_are_parsed() {
+        let pr_body = "Closes #12, fixes: #34 and resolves #12.\nRelated to #56. Prefixes #78.";
+        assert_eq!(closed_issues(pr_body), vec![12, 34]);
+    }
 }
@@ -115,6 +115,12 @@ impl Project {
             .collect()
     }
 
+    /// Keep only the packages with the given names.
+    pub(crate) fn retain_packages(&mut self, names: &[String]) {
+        self.packages
+            .retain(|p| names.iter().any(|name| *name == *p.name));
+    }
+
     /// Get all packages, including non-publishable.
     pub fn workspace_packages(&self) -> Vec<&Package> {
         self.packages.iter().collect()
@@ -0,0 +1,232 @@
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+/// Why `cargo publish` failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishErrorKind {
+    /// The registry refused the upload because too many crates or versions
+    /// were published in a short period of time.
+    RateLimited {
+        /// How long the registry asks to wait before trying again.
+        /// [`Option::None`] if the registry didn't say it.
+        retry_after: Option<Duration>,
+    },
+    /// The same version of the package is already in the registry.
+    /// See [`PublishError::uploaded_version`].
+    AlreadyUploaded,
+    /// The registry token is missing, invalid or doesn't have enough permissions.
+    Auth,
+    /// The package doesn't build or can't be packaged.
+    Verification,
+    /// The registry can't be reached.
+    Network,
+    Other,
+}
+
+/// Failure of `cargo publish`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishError {
+    kind: PublishErrorKind,
+    /// Stderr of `cargo publish`.
+    stderr: String,
+}
+
+impl PublishError {
+    /// Classify the failure from the stderr of `cargo publish`.
+    pub fn from_stderr(stderr: &str) -> Self {
+        Self::classify(stderr, Utc::now())
+    }
+
+    fn classify(stderr: &str, now: DateTime<Utc>) -> Self {
+        let kind = if is_rate_limited(stderr) {
+            PublishErrorKind::RateLimited {
+                retry_after: retry_after(stderr, now),
+            }
+        } else if already_uploaded_version(stderr).is_some() {
+            PublishErrorKind::AlreadyUploaded
+        } else if is_auth_error(stderr) {
+            PublishErrorKind::Auth
+        } else if stderr.contains("failed to verify package tarball")
+            || stderr.contains("could not compile")
+        {
+            PublishErrorKind::Verification
+        } else if is_network_error(stderr) {
+            PublishErrorKind::Network
+        } else {
+            PublishErrorKind::Other
+        };
+        Self {
+            kind,
+            stderr: stderr.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> PublishErrorKind {
+        self.kind
+    }
+
+    /// Version that the registry reported as already uploaded.
+    pub fn uploaded_version(&self) -> Option<&str> {
+        already_uploaded_version(&self.stderr)
+    }
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.kind {
+            PublishErrorKind::RateLimited { .. } => "the registry rate limited the upload",
+            PublishErrorKind::AlreadyUploaded => "the version is already uploaded",
+            PublishErrorKind::Auth => "the registry rejected the credentials",
+            PublishErrorKind::Verification => "the package verification failed",
+            PublishErrorKind::Network => "network error",
+            PublishErrorKind::Other => "cargo publish failed",
+        };
+        write!(f, "{reason}: {}", self.stderr)
+    }
+}
+
+impl std::error::Error for PublishError {}
+
+fn is_rate_limited(stderr: &str) -> bool {
+    stderr.contains("status 429") || stderr.contains("Too Many Requests")
+}
+
+/// Parse the version from messages like ``crate version `0.1.0` is already uploaded``.
+fn already_uploaded_version(stderr: &str) -> Option<&str> {
+    const PREFIX: &str = "crate version `";
+    const SUFFIX: &str = "` is already uploaded";
+    let start = stderr.find(PREFIX)? + PREFIX.len();
+    let rest = &stderr[start..];
+    let end = rest.find(SUFFIX)?;
+    Some(&rest[..end])
+}
+
+fn is_auth_error(stderr: &str) -> bool {
+    [
+        "status 401",
+        "status 403",
+        "no token found",
+        "please run `cargo login`",
+        "invalid authentication token",
+    ]
+    .iter()
+    .any(|pattern| stderr.contains(pattern))
+}
+
+fn is_network_error(stderr: &str) -> bool {
+    [
+        "spurious network error",
+        "failed to send request",
+        "Couldn't resolve host",
+        "Could not resolve host",
+        "Connection refused",
+        "Operation timed out",
+        "status 502",
+        "status 503",
+        "status 504",
+    ]
+    .iter()
+    .any(|pattern| stderr.contains(pattern))
+}
+
+/// Parse the wait time from messages like
+/// `Please try again after Tue, 04 Feb 2025 10:35:30 GMT or email help@crates.io`.
+fn retry_after(stderr: &str, now: DateTime<Utc>) -> Option<Duration> {
+    const PREFIX: &str = "try again after ";
+    let start = stderr.find(PREFIX)? + PREFIX.len();
+    let rest = &stderr[start..];
+    let end = [" or ", "\n"]
+        .iter()
+        .filter_map(|separator| rest.find(separator))
+        .min()
+        .unwrap_or(rest.len());
+    let date = rest[..end].trim().trim_end_matches('.');
+    let date = DateTime::parse_from_rfc2822(date).ok()?;
+    // If the date is in the past, we can retry immediately.
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-02-04T10:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn rate_limit_wait_is_parsed() {
+        let stderr = "error: failed to publish to registry at https://crates.io
+
+Caused by:
+  the remote server responded with an error (status 429 Too Many Requests): You have published too many new crates in a short period of time. Please try again after Tue, 04 Feb 2025 10:35:30 GMT or email help@crates.io to have your limit increased.";
+        let error = PublishError::classify(stderr, now());
+        assert_eq!(
+            error.kind(),
+            PublishErrorKind::RateLimited {
+                retry_after: Some(Duration::from_secs(330))
+            }
+        );
+    }
+
+    #[test]
+    fn rate_limit_without_date_has_no_wait() {
+        let error = PublishError::classify("status 429 Too Many Requests", now());
+        assert_eq!(
+            error.kind(),
+            PublishErrorKind::RateLimited { retry_after: None }
+        );
+    }
+
+    #[test]
+    fn already_uploaded_version_is_parsed() {
+        let stderr = "error: failed to publish to registry at https://crates.io
+
+Caused by:
+  the remote server responded with an error: crate version `0.1.0` is already uploaded";
+        let error = PublishError::classify(stderr, now());
+        assert_eq!(error.kind(), PublishErrorKind::AlreadyUploaded);
+        assert_eq!(error.uploaded_version(), Some("0.1.0"));
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let cases = [
+            (
+                "error: crate version `0.1.0` is already uploaded",
+                PublishErrorKind::AlreadyUploaded,
+            ),
+            (
+                "the remote server responded with an error (status 403 Forbidden): this token does not have the required permissions",
+                PublishErrorKind::Auth,
+            ),
+            (
+                "error: failed to verify package tarball",
+                PublishErrorKind::Verification,
+            ),
+            (
+                "warning: spurious network error (3 tries remaining): [6] Couldn't resolve host name",
+                PublishErrorKind::Network,
+            ),
+            (
+                "error: crate release-plz@0.1.0 already exists on crates.io index",
+                PublishErrorKind::Other,
+            ),
+            ("error: something unexpected", PublishErrorKind::Other),
+        ];
+        for (stderr, expected_kind) in cases {
+            assert_eq!(
+                PublishError::classify(stderr, now()).kind(),
+                expected_kind,
+                "{stderr}"
+            );
+        }
+    }
+}
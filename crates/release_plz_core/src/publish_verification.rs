@@ -0,0 +1,174 @@
+//! Check that the package uploaded to the registry is the package that release-plz released.
+
+use anyhow::Context as _;
+use cargo_metadata::{
+    Package,
+    camino::{Utf8Path, Utf8PathBuf},
+};
+use tracing::info;
+
+use crate::{
+    PackagePath as _, are_packages_equal,
+    cargo_vcs_info::read_sha1_from_cargo_vcs_info,
+    clone::{Cloner, ClonerSource, Crate},
+    fs_utils::Utf8TempDir,
+};
+
+/// Download the published version of `package` from the registry and check that:
+/// - its files are the same as the files of the local package.
+/// - its `.cargo_vcs_info.json` points to `commit`.
+///
+/// [`Option::None`] registry means crates.io.
+///
+/// The download and `cargo package --list` are blocking, so they run in a blocking task.
+/// `cargo package --list` can edit the `Cargo.lock` of the workspace, so the caller
+/// must make sure that no other cargo command uses the workspace in the meantime.
+pub async fn verify_published_package(
+    package: &Package,
+    registry: Option<&str>,
+    repo_dir: &Utf8Path,
+    commit: &str,
+) -> anyhow::Result<()> {
+    let package = package.clone();
+    let registry = registry.map(str::to_string);
+    let repo_dir = repo_dir.to_path_buf();
+    let commit = commit.to_string();
+    tokio::task::spawn_blocking(move || {
+        verify_published_package_blocking(&package, registry.as_deref(), &repo_dir, &commit)
+    })
+    .await
+    .context("the verification of the published package panicked")?
+}
+
+fn verify_published_package_blocking(
+    package: &Package,
+    registry: Option<&str>,
+    repo_dir: &Utf8Path,
+    commit: &str,
+) -> anyhow::Result<()> {
+    info!(
+        "{} {}: verifying the published package",
+        package.name, package.version
+    );
+    let temp_dir = Utf8TempDir::new()?;
+    let registry_package_path =
+        download_published_package(package, registry, repo_dir, temp_dir.path())?;
+
+    let cargo_vcs_info_path = registry_package_path.join(".cargo_vcs_info.json");
+    let published_commit = read_sha1_from_cargo_vcs_info(&cargo_vcs_info_path).with_context(|| {
+        format!(
+            "the published package doesn't contain a valid {cargo_vcs_info_path:?} file, so release-plz can't check which commit was published"
+        )
+    })?;
+    anyhow::ensure!(
+        published_commit == commit,
+        "the published package was built from commit {published_commit}, but the release commit is {commit}"
+    );
+    // Remove the file, otherwise `cargo package --list` fails
+    fs_err::remove_file(cargo_vcs_info_path)?;
+
+    let local_package_path = package.package_path()?;
+    // `cargo package` can edit the `Cargo.lock` file, so we restore it.
+    let cargo_lock = CargoLockBackup::new(repo_dir)?;
+    let are_packages_equal = are_packages_equal(local_package_path, &registry_package_path)
+        .context("cannot compare the published package with the local package");
+    cargo_lock.restore()?;
+    anyhow::ensure!(
+        are_packages_equal?,
+        "the files of the published package are different from the files of the local package"
+    );
+    Ok(())
+}
+
+fn download_published_package(
+    package: &Package,
+    registry: Option<&str>,
+    repo_dir: &Utf8Path,
+    directory: &Utf8Path,
+) -> anyhow::Result<Utf8PathBuf> {
+    let source = match registry {
+        Some(registry) => ClonerSource::registry(registry),
+        None => ClonerSource::crates_io(),
+    };
+    // Use `=` to download exactly the published version.
+    let crate_ = Crate::new(
+        package.name.to_string(),
+        Some(format!("={}", package.version)),
+    );
+    let cloned_packages = Cloner::builder()
+        .with_directory(directory)
+        .with_source(source)
+        // Use the cargo configuration of the project, which can contain the registry.
+        .with_cargo_cwd(repo_dir.to_path_buf())
+        .build()
+        .context("can't build cloner")?
+        .clone(&[crate_])
+        .context("can't download the published package")?;
+    let (_, path) = cloned_packages.into_iter().next().with_context(|| {
+        format!(
+            "package {} {} not found in the registry",
+            package.name, package.version
+        )
+    })?;
+    Ok(path)
+}
+
+/// Content of the `Cargo.lock` file of the workspace before running cargo.
+struct CargoLockBackup {
+    path: Utf8PathBuf,
+    /// [`Option::None`] if the file doesn't exist.
+    content: Option<Vec<u8>>,
+}
+
+impl CargoLockBackup {
+    fn new(workspace_dir: &Utf8Path) -> anyhow::Result<Self> {
+        let path = workspace_dir.join("Cargo.lock");
+        let content = path.exists().then(|| fs_err::read(&path)).transpose()?;
+        Ok(Self { path, content })
+    }
+
+    /// Revert the changes that cargo made to the `Cargo.lock` file.
+    /// Unlike `git checkout`, this keeps the uncommitted changes that were there before.
+    fn restore(self) -> anyhow::Result<()> {
+        match &self.content {
+            Some(content) => {
+                if fs_err::read(&self.path).ok().as_ref() != Some(content) {
+                    fs_err::write(&self.path, content)
+                        .context("cannot revert changes introduced when comparing packages")?;
+                }
+            }
+            None => {
+                if self.path.exists() {
+                    fs_err::remove_file(&self.path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_lock_is_restored() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let cargo_lock = temp_dir.path().join("Cargo.lock");
+        fs_err::write(&cargo_lock, "uncommitted content").unwrap();
+
+        let backup = CargoLockBackup::new(temp_dir.path()).unwrap();
+        fs_err::write(&cargo_lock, "content written by cargo").unwrap();
+        backup.restore().unwrap();
+        assert_eq!(
+            fs_err::read_to_string(&cargo_lock).unwrap(),
+            "uncommitted content"
+        );
+
+        fs_err::remove_file(&cargo_lock).unwrap();
+        let backup = CargoLockBackup::new(temp_dir.path()).unwrap();
+        fs_err::write(&cargo_lock, "content written by cargo").unwrap();
+        backup.restore().unwrap();
+        assert!(!cargo_lock.exists());
+    }
+}
@@ -0,0 +1,58 @@
+//! Secrets that must never appear in the logs.
+
+use std::{borrow::Cow, sync::RwLock};
+
+use secrecy::{ExposeSecret as _, SecretString};
+
+/// Text that replaces the secrets.
+pub const REDACTED: &str = "[REDACTED]";
+
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Remember the secret, so that [`redact_secrets`] removes it from the logs.
+pub fn add_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap_or_else(|e| e.into_inner());
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+        // Replace longer secrets first, in case a secret contains another one.
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+}
+
+/// Same as [`add_secret`], for a [`SecretString`].
+pub fn add_secret_string(secret: &SecretString) {
+    add_secret(secret.expose_secret());
+}
+
+/// Replace all the known secrets of the text with [`REDACTED`].
+pub fn redact_secrets(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.read().unwrap_or_else(|e| e.into_inner());
+    let mut text = Cow::Borrowed(text);
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted() {
+        add_secret("redact_test_secret");
+        add_secret("redact_test_secret_longer");
+        add_secret("  ");
+        assert_eq!(
+            redact_secrets("token redact_test_secret_longer and redact_test_secret"),
+            format!("token {REDACTED} and {REDACTED}")
+        );
+        assert_eq!(redact_secrets("nothing to hide"), "nothing to hide");
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use cargo_metadata::camino::Utf8PathBuf;
+use secrecy::SecretString;
+
+/// Name of crates.io in the cargo configuration.
+pub const CRATES_IO_REGISTRY_NAME: &str = "crates-io";
+
+/// Where release-plz reads the token of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryTokenSource {
+    /// Name of the environment variable containing the token.
+    Env(String),
+    /// File containing the token.
+    File(Utf8PathBuf),
+}
+
+/// Publish settings of a cargo registry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryConfig {
+    /// If [`Option::None`], the token is read from the cargo configuration.
+    token: Option<RegistryTokenSource>,
+    /// If [`Option::None`], the `publish_timeout` of the release is used.
+    publish_timeout: Option<Duration>,
+    /// If [`Option::None`], the `no_verify` setting of the package is used.
+    no_verify: Option<bool>,
+}
+
+impl RegistryConfig {
+    pub fn with_token(mut self, token: RegistryTokenSource) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn with_publish_timeout(mut self, publish_timeout: Duration) -> Self {
+        self.publish_timeout = Some(publish_timeout);
+        self
+    }
+
+    pub fn with_no_verify(mut self, no_verify: bool) -> Self {
+        self.no_verify = Some(no_verify);
+        self
+    }
+
+    pub fn publish_timeout(&self) -> Option<Duration> {
+        self.publish_timeout
+    }
+
+    pub fn no_verify(&self) -> Option<bool> {
+        self.no_verify
+    }
+
+    /// Read the token from its source.
+    /// Return an error if the source is configured but the token can't be read,
+    /// so that release-plz doesn't silently publish with another token.
+    pub fn token(&self) -> anyhow::Result<Option<SecretString>> {
+        let token = match &self.token {
+            None => return Ok(None),
+            Some(RegistryTokenSource::Env(env_var)) => {
+                std::env::var(env_var).with_context(|| {
+                    format!("can't read registry token from environment variable {env_var}")
+                })?
+            }
+            Some(RegistryTokenSource::File(path)) => fs_err::read_to_string(path)
+                .with_context(|| format!("can't read registry token from file {path:?}"))?,
+        };
+        let token = token.trim();
+        anyhow::ensure!(!token.is_empty(), "the registry token is empty");
+        Ok(Some(token.to_string().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::ExposeSecret as _;
+
+    use crate::fs_utils::Utf8TempDir;
+
+    use super::*;
+
+    #[test]
+    fn token_is_read_from_file() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let token_path = temp_dir.path().join("token");
+        fs_err::write(&token_path, "my_token\n").unwrap();
+        let config = RegistryConfig::default().with_token(RegistryTokenSource::File(token_path));
+        let token = config.token().unwrap().unwrap();
+        assert_eq!(token.expose_secret(), "my_token");
+    }
+
+    #[test]
+    fn missing_token_env_var_is_an_error() {
+        let config = RegistryConfig::default().with_token(RegistryTokenSource::Env(
+            "RELEASE_PLZ_TEST_MISSING_REGISTRY_TOKEN".to_string(),
+        ));
+        let error = config.token().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "can't read registry token from environment variable RELEASE_PLZ_TEST_MISSING_REGISTRY_TOKEN"
+        );
+    }
+
+    #[test]
+    fn token_is_none_without_source() {
+        assert!(RegistryConfig::default().token().unwrap().is_none());
+    }
+}
@@ -0,0 +1,291 @@
+//! Periods of time when release-plz doesn't publish packages,
+//! e.g. during holidays or before an important event.
+
+use std::fmt;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Datelike as _, TimeDelta, Timelike as _, Utc};
+
+/// Longest duration of a recurring freeze window.
+/// It limits the number of minutes to check when looking for the start of an active window.
+const MAX_RECURRING_DURATION: TimeDelta = TimeDelta::days(31);
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_OF_WEEK_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreezeWindow {
+    schedule: FreezeSchedule,
+    /// Why the releases are frozen. Shown in the release PR and in the `release` error.
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FreezeSchedule {
+    /// Freeze between two instants.
+    Range {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// Freeze for `duration`, every time the cron expression matches.
+    Recurring {
+        cron: CronSchedule,
+        duration: TimeDelta,
+    },
+}
+
+impl FreezeWindow {
+    /// Freeze from `start` (included) to `end` (excluded).
+    pub fn range(start: DateTime<Utc>, end: DateTime<Utc>) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            start < end,
+            "the start of the freeze window ({start}) must be before its end ({end})"
+        );
+        Ok(Self {
+            schedule: FreezeSchedule::Range { start, end },
+            reason: None,
+        })
+    }
+
+    /// Freeze for `duration` every time the cron expression matches.
+    /// The cron expression has 5 fields (minute, hour, day of month, month, day of week)
+    /// and it's evaluated in UTC.
+    pub fn recurring(cron: &str, duration: std::time::Duration) -> anyhow::Result<Self> {
+        let cron = CronSchedule::parse(cron)
+            .with_context(|| format!("invalid cron expression `{cron}`"))?;
+        let duration = TimeDelta::from_std(duration)
+            .ok()
+            .filter(|duration| *duration > TimeDelta::zero() && *duration <= MAX_RECURRING_DURATION)
+            .context(
+                "the duration of a recurring freeze window must be between 1 second and 31 days",
+            )?;
+        Ok(Self {
+            schedule: FreezeSchedule::Recurring { cron, duration },
+            reason: None,
+        })
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// If the window is active at `now`, return when it ends.
+    fn active_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.schedule {
+            FreezeSchedule::Range { start, end } => (*start <= now && now < *end).then_some(*end),
+            FreezeSchedule::Recurring { cron, duration } => {
+                // Look for the most recent start of the window, going back minute by minute.
+                let now_minute = now.with_second(0)?.with_nanosecond(0)?;
+                let mut start = now_minute;
+                while start + *duration > now {
+                    if cron.matches(start) {
+                        return Some(start + *duration);
+                    }
+                    start -= TimeDelta::minutes(1);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Freeze window that is currently active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveFreeze {
+    pub reason: Option<String>,
+    /// When the freeze ends.
+    pub end: DateTime<Utc>,
+}
+
+impl fmt::Display for ActiveFreeze {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "release freeze until {}",
+            self.end.format("%Y-%m-%d %H:%M UTC")
+        )?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the freeze that is active at `now`.
+/// If multiple windows are active, return the one that ends last.
+pub fn active_freeze(windows: &[FreezeWindow], now: DateTime<Utc>) -> Option<ActiveFreeze> {
+    windows
+        .iter()
+        .filter_map(|window| {
+            window.active_until(now).map(|end| ActiveFreeze {
+                reason: window.reason.clone(),
+                end,
+            })
+        })
+        .max_by_key(|freeze| freeze.end)
+}
+
+/// Minimal cron expression, evaluated in UTC.
+/// Each field is a bit mask of the allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day of month field is different from `*`.
+    day_of_month_restricted: bool,
+    /// Whether the day of week field is different from `*`.
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    fn parse(expression: &str) -> anyhow::Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            anyhow::bail!(
+                "expected 5 fields (minute, hour, day of month, month, day of week), found {}",
+                fields.len()
+            );
+        };
+        let mut days_of_week =
+            parse_field(day_of_week, 0, 7, DAY_OF_WEEK_NAMES).context("invalid day of week")?;
+        // Both 0 and 7 are Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[]).context("invalid minute")?,
+            hours: parse_field(hour, 0, 23, &[]).context("invalid hour")?,
+            days_of_month: parse_field(day_of_month, 1, 31, &[]).context("invalid day of month")?,
+            months: parse_field(month, 1, 12, MONTH_NAMES).context("invalid month")?,
+            days_of_week,
+            day_of_month_restricted: day_of_month != "*",
+            day_of_week_restricted: day_of_week != "*",
+        })
+    }
+
+    fn matches(&self, time: DateTime<Utc>) -> bool {
+        let is_set = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day_of_month = is_set(self.days_of_month, time.day());
+        let day_of_week = is_set(self.days_of_week, time.weekday().num_days_from_sunday());
+        // Like in cron, if both day fields are restricted, matching one of them is enough.
+        let day = if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        };
+        day && is_set(self.minutes, time.minute())
+            && is_set(self.hours, time.hour())
+            && is_set(self.months, time.month())
+    }
+}
+
+/// Parse a cron field, such as `1-5`, `*/15` or `MON,WED`.
+/// `names` are the names of the values, starting from `min`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> anyhow::Result<u64> {
+    let parse_value = |value: &str| -> anyhow::Result<u32> {
+        let value = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+        {
+            Some(position) => min + position as u32,
+            None => value
+                .parse()
+                .with_context(|| format!("`{value}` is not a number"))?,
+        };
+        anyhow::ensure!(
+            (min..=max).contains(&value),
+            "`{value}` is not between {min} and {max}"
+        );
+        Ok(value)
+    };
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .with_context(|| format!("`{step}` is not a number"))?;
+                anyhow::ensure!(step > 0, "the step must be greater than 0");
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (parse_value(start)?, parse_value(end)?),
+            // `5/15` means "from 5 to the end, every 15".
+            None if step > 1 => (parse_value(range)?, max),
+            None => {
+                let value = parse_value(range)?;
+                (value, value)
+            }
+        };
+        anyhow::ensure!(start <= end, "`{range}` is an empty range");
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    #[test]
+    fn range_window_is_active_between_start_and_end() {
+        let window = FreezeWindow::range(utc("2024-12-20T00:00:00Z"), utc("2025-01-06T00:00:00Z"))
+            .unwrap()
+            .with_reason("holidays");
+        let windows = [window];
+        assert_eq!(active_freeze(&windows, utc("2024-12-19T23:59:59Z")), None);
+        let freeze = active_freeze(&windows, utc("2024-12-25T10:00:00Z")).unwrap();
+        assert_eq!(
+            freeze.to_string(),
+            "release freeze until 2025-01-06 00:00 UTC (holidays)"
+        );
+        assert_eq!(active_freeze(&windows, utc("2025-01-06T00:00:00Z")), None);
+        assert!(
+            FreezeWindow::range(utc("2025-01-06T00:00:00Z"), utc("2024-12-20T00:00:00Z")).is_err()
+        );
+    }
+
+    #[test]
+    fn recurring_window_is_active_after_cron_match() {
+        // Every Friday from 16:00 for 64 hours, i.e. until Monday at 8:00.
+        let hours_64 = std::time::Duration::from_secs(64 * 60 * 60);
+        let windows = [FreezeWindow::recurring("0 16 * * FRI", hours_64).unwrap()];
+        // Friday 2024-06-07.
+        assert_eq!(active_freeze(&windows, utc("2024-06-07T15:59:00Z")), None);
+        let freeze = active_freeze(&windows, utc("2024-06-08T12:30:00Z")).unwrap();
+        assert_eq!(freeze.end, utc("2024-06-10T08:00:00Z"));
+        assert_eq!(active_freeze(&windows, utc("2024-06-10T08:00:00Z")), None);
+    }
+
+    #[test]
+    fn cron_fields_are_parsed() {
+        let cron = CronSchedule::parse("*/15 9-17 1,15 jan-mar 7").unwrap();
+        assert_eq!(cron.minutes, 1 | 1 << 15 | 1 << 30 | 1 << 45);
+        assert_eq!(cron.hours, 0b11_1111_1110_0000_0000);
+        assert_eq!(cron.months, 0b1110);
+        assert_eq!(cron.days_of_week, 1 | 1 << 7);
+        // 2024-01-07 is a Sunday, so it matches even if it isn't the 1st or the 15th.
+        assert!(cron.matches(utc("2024-01-07T09:45:00Z")));
+        assert!(!cron.matches(utc("2024-01-08T09:45:00Z")));
+        let error = CronSchedule::parse("0 25 * * *").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid hour: `25` is not between 0 and 23"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Conditions that the merged release PR must meet before `release` publishes the packages.
+
+use anyhow::Context as _;
+
+use crate::{GitClient, GitPr, git::forge::CommitStatus};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseGates {
+    /// Labels that the release PR must have.
+    labels: Vec<String>,
+    /// Minimum number of users that approved the release PR.
+    approvals: u32,
+    /// If true, the statuses and checks of the last commit of the release PR must be successful.
+    passing_status: bool,
+}
+
+impl ReleaseGates {
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn with_approvals(mut self, approvals: u32) -> Self {
+        self.approvals = approvals;
+        self
+    }
+
+    pub fn with_passing_status(mut self, passing_status: bool) -> Self {
+        self.passing_status = passing_status;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.approvals == 0 && !self.passing_status
+    }
+
+    /// Return the reasons why the release PR doesn't meet the gates.
+    pub(crate) async fn unmet_gates(
+        &self,
+        git_client: &GitClient,
+        pr: &GitPr,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut unmet_gates = self.missing_labels(pr);
+        if self.approvals > 0 {
+            let approvers = git_client
+                .pr_approvers(pr.number)
+                .await
+                .with_context(|| format!("can't retrieve the reviews of PR #{}", pr.number))?;
+            if approvers.len() < self.approvals as usize {
+                unmet_gates.push(format!(
+                    "the release PR #{} has {} approval(s), but {} are required",
+                    pr.number,
+                    approvers.len(),
+                    self.approvals
+                ));
+            }
+        }
+        if self.passing_status {
+            let commit = &pr.head.sha;
+            let status = git_client
+                .commit_status(commit)
+                .await
+                .with_context(|| format!("can't retrieve the status of commit {commit}"))?;
+            let problem = match status {
+                CommitStatus::Success => None,
+                CommitStatus::Pending => Some("are still running"),
+                CommitStatus::Failure => Some("failed"),
+                CommitStatus::Missing => Some("don't exist"),
+            };
+            if let Some(problem) = problem {
+                unmet_gates.push(format!(
+                    "the checks of commit {commit} of the release PR #{} {problem}",
+                    pr.number
+                ));
+            }
+        }
+        Ok(unmet_gates)
+    }
+
+    fn missing_labels(&self, pr: &GitPr) -> Vec<String> {
+        let pr_labels = pr.label_names();
+        self.labels
+            .iter()
+            .filter(|label| !pr_labels.contains(&label.as_str()))
+            .map(|label| {
+                format!(
+                    "the release PR #{} doesn't have the label `{label}`",
+                    pr.number
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_labels_are_reported() {
+        let pr: GitPr = serde_json::from_value(serde_json::json!({
+            "user": { "login": "release-plz" },
+            "number": 42,
+            "html_url": "https://github.com/owner/repo/pull/42",
+            "head": { "ref": "release-plz-2024", "sha": "abc" },
+            "title": "chore: release",
+            "body": null,
+            "labels": [{ "name": "approved", "id": 1 }],
+        }))
+        .unwrap();
+        let gates =
+            ReleaseGates::default().with_labels(vec!["approved".to_string(), "qa".to_string()]);
+        assert_eq!(
+            gates.missing_labels(&pr),
+            vec!["the release PR #42 doesn't have the label `qa`"]
+        );
+        assert!(!gates.is_empty());
+        assert!(ReleaseGates::default().is_empty());
+    }
+}
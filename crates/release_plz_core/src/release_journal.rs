@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Context as _;
+use cargo_metadata::{
+    Package,
+    camino::{Utf8Path, Utf8PathBuf},
+    semver::Version,
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::CRATES_IO_REGISTRY_NAME;
+
+/// Steps that `release` completed for each package.
+///
+/// The journal is saved to disk after every step, so that
+/// `release --resume` can pick up an interrupted release
+/// exactly where it stopped.
+/// Packages can be released concurrently, so the journal can be shared between tasks.
+#[derive(Debug, Default)]
+pub struct ReleaseJournal {
+    /// File where the journal is saved.
+    /// If [`Option::None`], the journal is kept in memory only.
+    path: Option<Utf8PathBuf>,
+    content: Mutex<JournalContent>,
+}
+
+/// Content of the journal file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct JournalContent {
+    /// The key is the package name.
+    packages: BTreeMap<String, PackageJournal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackageJournal {
+    /// Version of the package that release-plz is releasing.
+    version: Version,
+    /// Registries where the package was uploaded.
+    published: BTreeSet<String>,
+    /// Registries where the uploaded package passed the `publish_verify_upload` check.
+    #[serde(default)]
+    upload_verified: BTreeSet<String>,
+    /// The git tag was created locally.
+    tagged: bool,
+    /// The git tag was pushed to the remote.
+    tag_pushed: bool,
+    /// The git forge release was created.
+    git_release_created: bool,
+    /// The assets of the git forge release were uploaded.
+    #[serde(default)]
+    git_release_assets_uploaded: bool,
+    /// All the release steps of the package are done.
+    completed: bool,
+}
+
+impl PackageJournal {
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            published: BTreeSet::new(),
+            upload_verified: BTreeSet::new(),
+            tagged: false,
+            tag_pushed: false,
+            git_release_created: false,
+            git_release_assets_uploaded: false,
+            completed: false,
+        }
+    }
+
+    pub fn is_published(&self, registry: Option<&str>) -> bool {
+        self.published.contains(registry_key(registry))
+    }
+
+    pub fn is_upload_verified(&self, registry: Option<&str>) -> bool {
+        self.upload_verified.contains(registry_key(registry))
+    }
+
+    pub fn is_tagged(&self) -> bool {
+        self.tagged
+    }
+
+    pub fn is_tag_pushed(&self) -> bool {
+        self.tag_pushed
+    }
+
+    pub fn is_git_release_created(&self) -> bool {
+        self.git_release_created
+    }
+
+    pub fn are_git_release_assets_uploaded(&self) -> bool {
+        self.git_release_assets_uploaded
+    }
+
+    pub fn set_published(&mut self, registry: Option<&str>) {
+        self.published.insert(registry_key(registry).to_string());
+    }
+
+    pub fn set_upload_verified(&mut self, registry: Option<&str>) {
+        self.upload_verified
+            .insert(registry_key(registry).to_string());
+    }
+
+    pub fn set_tagged(&mut self) {
+        self.tagged = true;
+    }
+
+    pub fn set_tag_pushed(&mut self) {
+        self.tag_pushed = true;
+    }
+
+    pub fn set_git_release_created(&mut self) {
+        self.git_release_created = true;
+    }
+
+    pub fn set_git_release_assets_uploaded(&mut self) {
+        self.git_release_assets_uploaded = true;
+    }
+
+    pub fn set_completed(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// [`Option::None`] means crates.io.
+fn registry_key(registry: Option<&str>) -> &str {
+    registry.unwrap_or(CRATES_IO_REGISTRY_NAME)
+}
+
+impl ReleaseJournal {
+    /// Load the journal from `path`.
+    /// If the file doesn't exist, the journal is empty.
+    pub fn load(path: &Utf8Path) -> anyhow::Result<Self> {
+        let content: JournalContent = if path.exists() {
+            let content = fs_err::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("can't parse release journal {path:?}"))?
+        } else {
+            JournalContent::default()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            content: Mutex::new(content),
+        })
+    }
+
+    /// Progress of the release of the given version of the package.
+    pub fn get(&self, package_name: &str, version: &Version) -> Option<PackageJournal> {
+        self.content()
+            .packages
+            .get(package_name)
+            .filter(|p| &p.version == version)
+            .cloned()
+    }
+
+    /// Progress of a release of the package that was interrupted before completing all the steps.
+    pub fn pending(&self, package_name: &str, version: &Version) -> Option<PackageJournal> {
+        self.get(package_name, version).filter(|p| !p.completed)
+    }
+
+    /// Start tracking the release of the package from scratch,
+    /// discarding any previous progress.
+    pub fn start(&self, package: &Package) -> anyhow::Result<()> {
+        let mut content = self.content();
+        content.packages.insert(
+            package.name.to_string(),
+            PackageJournal::new(package.version.clone()),
+        );
+        self.save(&content)
+    }
+
+    /// Record a release step of the package and save the journal.
+    pub fn record(
+        &self,
+        package: &Package,
+        step: impl FnOnce(&mut PackageJournal),
+    ) -> anyhow::Result<()> {
+        let mut content = self.content();
+        let entry = content
+            .packages
+            .entry(package.name.to_string())
+            .or_insert_with(|| PackageJournal::new(package.version.clone()));
+        if entry.version != package.version {
+            *entry = PackageJournal::new(package.version.clone());
+        }
+        step(entry);
+        self.save(&content)
+    }
+
+    fn content(&self) -> MutexGuard<'_, JournalContent> {
+        // The content is always in a consistent state, even if a task panicked.
+        self.content.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn save(&self, content: &JournalContent) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(content).context("can't serialize release journal")?;
+        fs_err::write(path, content).context("can't write release journal")?;
+        debug!("release journal saved to {path:?}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_package::FakePackage;
+
+    use crate::fs_utils::Utf8TempDir;
+
+    use super::*;
+
+    fn package(version: &str) -> Package {
+        let mut package: Package = FakePackage::new("my_package").into();
+        package.version = Version::parse(version).unwrap();
+        package
+    }
+
+    #[test]
+    fn journal_is_saved_and_loaded() {
+        let temp_dir = Utf8TempDir::new().unwrap();
+        let path = temp_dir.path().join("release-plz").join("journal.json");
+        let package = package("0.1.0");
+
+        let journal = ReleaseJournal::load(&path).unwrap();
+        journal.start(&package).unwrap();
+        journal.record(&package, |p| p.set_published(None)).unwrap();
+        journal
+            .record(&package, PackageJournal::set_tagged)
+            .unwrap();
+
+        let journal = ReleaseJournal::load(&path).unwrap();
+        let entry = journal.pending("my_package", &package.version).unwrap();
+        assert!(entry.is_published(None));
+        assert!(!entry.is_published(Some("my_registry")));
+        assert!(!entry.is_upload_verified(None));
+        assert!(entry.is_tagged());
+        assert!(!entry.is_tag_pushed());
+        assert!(!entry.is_git_release_created());
+    }
+
+    #[test]
+    fn new_version_discards_previous_progress() {
+        let journal = ReleaseJournal::default();
+        let old_package = package("0.1.0");
+        journal
+            .record(&old_package, PackageJournal::set_tagged)
+            .unwrap();
+
+        let new_package = package("0.2.0");
+        journal
+            .record(&new_package, |p| p.set_published(None))
+            .unwrap();
+
+        assert!(journal.get("my_package", &old_package.version).is_none());
+        let entry = journal.get("my_package", &new_package.version).unwrap();
+        assert!(entry.is_published(None));
+        assert!(!entry.is_tagged());
+    }
+
+    #[test]
+    fn completed_release_is_not_pending() {
+        let journal = ReleaseJournal::default();
+        let package = package("0.1.0");
+        journal
+            .record(&package, PackageJournal::set_completed)
+            .unwrap();
+
+        assert!(journal.get("my_package", &package.version).is_some());
+        assert!(journal.pending("my_package", &package.version).is_none());
+    }
+}
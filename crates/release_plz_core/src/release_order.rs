@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use cargo_metadata::{Dependency, DependencyKind, Package};
 use tracing::debug;
 
@@ -17,6 +19,52 @@ pub fn release_order<'a>(packages: &'a [&Package]) -> anyhow::Result<Vec<&'a Pac
     Ok(order)
 }
 
+/// Group packages in levels that can be released one after the other.
+/// Each package is placed in the level after the one of its last dependency,
+/// so packages of the same level don't depend on each other and can be released concurrently.
+/// Return an error if a circular dependency is detected.
+pub fn release_levels<'a>(packages: &'a [&Package]) -> anyhow::Result<Vec<Vec<&'a Package>>> {
+    let order = release_order(packages)?;
+    let mut package_levels: HashMap<&str, usize> = HashMap::new();
+    let mut levels: Vec<Vec<&Package>> = vec![];
+    for pkg in order {
+        // Dependencies come first in the release order, so their level is already known.
+        let level = packages
+            .iter()
+            .filter(|dep| depends_on(pkg, dep))
+            .filter_map(|dep| package_levels.get(dep.name.as_str()))
+            .max()
+            .map_or(0, |dep_level| dep_level + 1);
+        package_levels.insert(&pkg.name, level);
+        if levels.len() <= level {
+            levels.resize_with(level + 1, Vec::new);
+        }
+        levels[level].push(pkg);
+    }
+    debug!(
+        "Release levels: {:?}",
+        levels
+            .iter()
+            .map(|level| level.iter().map(|p| &p.name).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    );
+    Ok(levels)
+}
+
+/// Return true if any of the `packages` needs `pkg` to be released before it.
+pub fn is_dependency_of_any(pkg: &Package, packages: &[&Package]) -> bool {
+    packages.iter().any(|p| depends_on(p, pkg))
+}
+
+/// Return true if `dep` needs to be released before `pkg`.
+fn depends_on(pkg: &Package, dep: &Package) -> bool {
+    pkg.name != dep.name
+        && pkg
+            .dependencies
+            .iter()
+            .any(|d| d.name == *dep.name && should_dep_be_released_before(d, pkg))
+}
+
 /// The `passed` argument is used to track packages that you already visited to
 /// detect circular dependencies.
 fn release_order_inner<'a>(
@@ -125,6 +173,14 @@ mod tests {
         FakeDependency::new(name).dev()
     }
 
+    fn levels<'a>(pkgs: &'a [&'a Package]) -> Vec<Vec<&'a str>> {
+        release_levels(pkgs)
+            .unwrap()
+            .iter()
+            .map(|level| level.iter().map(|p| p.name.as_str()).collect())
+            .collect()
+    }
+
     fn order<'a>(pkgs: &'a [&'a Package]) -> Vec<&'a str> {
         release_order(pkgs)
             .unwrap()
@@ -229,4 +285,52 @@ mod tests {
         let pkgs = [&a, &pkg("b", &[dep("a")])];
         assert_eq!(order(&pkgs), ["a", "b"]);
     }
+
+    /// A────►C
+    /// │     ▲
+    /// └─►B──┘
+    #[test]
+    fn three_packages_are_grouped_in_levels() {
+        let pkgs = [
+            &pkg("a", &[dep("b"), dep("c")]),
+            &pkg("b", &[dep("c")]),
+            &pkg("c", &[]),
+        ];
+        assert_eq!(levels(&pkgs), [vec!["c"], vec!["b"], vec!["a"]]);
+    }
+
+    /// A──►C◄──B   D
+    #[test]
+    fn independent_packages_are_in_the_same_level() {
+        let pkgs = [
+            &pkg("a", &[dep("c")]),
+            &pkg("b", &[dep("c")]),
+            &pkg("c", &[]),
+            &pkg("d", &[]),
+        ];
+        assert_eq!(levels(&pkgs), [vec!["c", "d"], vec!["a", "b"]]);
+    }
+
+    /// A────►B (dev dependency)
+    #[test]
+    fn dev_dependencies_dont_create_levels() {
+        let a = pkg("a", &[dev_dep("b")]);
+        let b = pkg("b", &[]);
+        let pkgs = [&a, &b];
+        assert_eq!(levels(&pkgs), [vec!["a", "b"]]);
+        assert!(!is_dependency_of_any(&b, &pkgs));
+    }
+
+    /// A──►B──►C
+    /// Only the packages that other packages depend on wait for the registry index.
+    #[test]
+    fn leaf_package_is_not_a_dependency_of_other_packages() {
+        let a = pkg("a", &[dep("b")]);
+        let b = pkg("b", &[dep("c")]);
+        let c = pkg("c", &[]);
+        let pkgs = [&a, &b, &c];
+        assert!(!is_dependency_of_any(&a, &pkgs));
+        assert!(is_dependency_of_any(&b, &pkgs));
+        assert!(is_dependency_of_any(&c, &pkgs));
+    }
 }
@@ -0,0 +1,145 @@
+//! Tell the contributors that their work was released, by commenting on and labeling
+//! the PRs included in a release and the issues that they close.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context as _;
+use cargo_metadata::Package;
+use tracing::{debug, info, warn};
+
+use crate::{
+    GitClient,
+    git::forge::IssueOrPr,
+    pr_parser::{Pr, closed_issues},
+    tera::{render_template, tera_context},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleasedPrsConfig {
+    /// Tera template of the comment posted on the released PRs and issues.
+    /// If [`Option::None`], release-plz doesn't comment.
+    comment_template: Option<String>,
+    /// Label added to the released PRs and issues.
+    label: Option<String>,
+}
+
+impl ReleasedPrsConfig {
+    pub fn with_comment_template(mut self, comment_template: impl Into<String>) -> Self {
+        self.comment_template = Some(comment_template.into());
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comment_template.is_none() && self.label.is_none()
+    }
+
+    /// Comment on and label the PRs included in the release of the package
+    /// and the issues they close.
+    /// A failure on a PR or issue doesn't stop the others.
+    pub(crate) async fn update(
+        &self,
+        git_client: &GitClient,
+        package: &Package,
+        prs: &[Pr],
+    ) -> anyhow::Result<()> {
+        if self.is_empty() || prs.is_empty() {
+            return Ok(());
+        }
+        let comment = self
+            .comment_template
+            .as_deref()
+            .map(|template| released_comment(template, package))
+            .transpose()?;
+        let marker = comment_marker(package);
+        let mut items = BTreeSet::new();
+        for pr in prs {
+            items.insert(IssueOrPr::Pr(pr.number));
+            match git_client.get_pr_info(pr.number).await {
+                Ok(git_pr) => items.extend(
+                    closed_issues(git_pr.body.as_deref().unwrap_or_default())
+                        .into_iter()
+                        .map(IssueOrPr::Issue),
+                ),
+                Err(e) => warn!(
+                    "can't retrieve the issues closed by PR #{}: {e:?}",
+                    pr.number
+                ),
+            }
+        }
+        for item in items {
+            if let Err(e) = self
+                .update_item(git_client, item, comment.as_deref(), &marker)
+                .await
+            {
+                warn!(
+                    "can't update {item:?} released in {} {}: {e:?}",
+                    package.name, package.version
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn update_item(
+        &self,
+        git_client: &GitClient,
+        item: IssueOrPr,
+        comment: Option<&str>,
+        marker: &str,
+    ) -> anyhow::Result<()> {
+        if let Some(comment) = comment {
+            let comments = git_client.comments(item).await?;
+            if comments.iter().any(|c| c.contains(marker)) {
+                debug!("{item:?} already contains the release comment");
+            } else {
+                git_client.add_comment(item, comment).await?;
+                info!("commented on {item:?}");
+            }
+        }
+        if let Some(label) = &self.label {
+            git_client.add_label(item, label).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Render the comment template.
+/// The comment ends with a hidden marker, used to avoid commenting twice
+/// when release-plz runs again.
+fn released_comment(template: &str, package: &Package) -> anyhow::Result<String> {
+    let context = tera_context(&package.name, &package.version.to_string());
+    let comment = render_template(template, &context, "released_pr_comment")
+        .context("failed to render the comment of the released PRs")?;
+    Ok(format!("{comment}\n\n{}", comment_marker(package)))
+}
+
+fn comment_marker(package: &Package) -> String {
+    format!(
+        "<!-- release-plz: released {} {} -->",
+        package.name, package.version
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_package::FakePackage;
+
+    use super::*;
+
+    #[test]
+    fn comment_ends_with_marker() {
+        let package: Package = FakePackage::new("my_package").into();
+        let comment =
+            released_comment("Released in `{{ package }}` {{ version }}", &package).unwrap();
+        expect_test::expect![[r#"
+            Released in `my_package` 0.1.0
+
+            <!-- release-plz: released my_package 0.1.0 -->"#]]
+        .assert_eq(&comment);
+    }
+}